@@ -0,0 +1,5 @@
+pub mod models;
+pub mod services;
+
+#[cfg(test)]
+mod test_utils;
@@ -1,21 +1,15 @@
-mod models;
-mod services;
-use std::process;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    process,
+    time::Instant,
+};
 
 use clap::Parser;
-use ctrlc;
-
-// Ensure that only one branching heuristic is enabled
-fn ensure_one_bh_enabled() {
-    let features = [
-        cfg!(feature = "bh_chb"),
-        cfg!(feature = "bh_lrb"),
-        cfg!(feature = "bh_vsids"),
-    ];
-
-    let enabled_features = features.iter().filter(|&f| *f).count();
-    assert!(enabled_features == 1)
-}
+use maplesat_rust::models::{
+    branching_heuristic::Branching, lbool::LBool, lit::Lit, restart::Restart,
+    simp_solver::SimpSolver, solver::Solver, var::Var, walker::WalkScoring,
+};
 
 fn interrupt() {
     println!("Interrupted!");
@@ -35,9 +29,31 @@ struct Args {
     verb: i8,
 
     /// Completely turn on/off any preprocessing.
-    #[arg(short, long, default_value_t = true)]
+    #[arg(short, long, default_value_t = true, action = clap::ArgAction::Set)]
     pre: bool,
 
+    /// Perform variable elimination.
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    elim: bool,
+
+    /// Substitute equivalent literals.
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    equiv: bool,
+
+    /// Allow a variable elimination step to grow by a number of clauses.
+    #[arg(long, default_value_t = 0)]
+    grow: i32,
+
+    /// Variables are not eliminated if it produces a resolvent with a length
+    /// above this limit (-1=no limit).
+    #[arg(long, default_value_t = 20, allow_negative_numbers = true)]
+    cl_lim: i32,
+
+    /// Do not check subsumption against clauses larger than this (-1=no
+    /// limit).
+    #[arg(long, default_value_t = 1000, allow_negative_numbers = true)]
+    sub_lim: i32,
+
     /// If given, stop after preprocessing and write the result to this file.
     #[arg(short, long)]
     dimacs: Option<String>,
@@ -50,17 +66,281 @@ struct Args {
     #[arg(short, long, default_value_t = i32::MAX)]
     mem_lim: i32,
 
+    /// Branching heuristic (defaults to the one enabled by the cargo features).
+    #[arg(long, value_enum, default_value_t = Branching::default())]
+    branching: Branching,
+
+    /// Restart policy (of the stable mode with mode switching).
+    #[arg(long, value_enum, default_value_t = Restart::default())]
+    restart: Restart,
+
+    /// Alternate between a focused and a stable search mode.
+    #[arg(long, default_value_t = false)]
+    mode_switching: bool,
+
+    /// Branching heuristic of the stable mode.
+    #[arg(long, value_enum, default_value_t = Branching::default())]
+    stable_branching: Branching,
+
+    /// Use target phases (0=none, 1=in stable mode, 2=always).
+    #[arg(long, default_value_t = 1)]
+    target_phase: i32,
+
+    /// Periodically reset the saved phases.
+    #[arg(long, default_value_t = false)]
+    rephase: bool,
+
+    /// Local search run when rephasing.
+    #[arg(long, value_enum, default_value_t = WalkScoring::default())]
+    walk: WalkScoring,
+
+    /// Minimize the core and tier2 learnt clauses at restarts.
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    lcm: bool,
+
+    /// Probe failed literals before the search and periodically at restarts.
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    probe: bool,
+
+    /// Propagations allowed in one probing round (-1=no limit).
+    #[arg(long, default_value_t = 100000, allow_negative_numbers = true)]
+    probe_budget: i64,
+
+    /// Backtrack chronologically when the backjump is at least this many
+    /// levels (-1=never).
+    #[arg(long, default_value_t = 100, allow_negative_numbers = true)]
+    chrono: i32,
+
+    /// Conflicts before chronological backtracking is used (-1=always).
+    #[arg(long, default_value_t = 4000, allow_negative_numbers = true)]
+    conf_to_chrono: i64,
+
     /// If given, use the assumptions in the file.
     #[arg(short, long)]
     assumptions: Option<String>,
 }
 
-fn main() {
-    ensure_one_bh_enabled();
+fn print_stats(simp: &SimpSolver, start: Instant) {
+    let solver = &simp.solver;
+    let cpu_time = start.elapsed().as_secs_f64();
+    println!(
+        "c eliminated vars       : {:<12}   ({} merges)",
+        simp.eliminated_vars, simp.merges
+    );
+    println!("c substituted vars      : {}", simp.substituted_vars);
+    println!(
+        "c subsumed clauses      : {:<12}   ({} literals strengthened away)",
+        simp.subsumed_clauses, simp.deleted_literals
+    );
+    if solver.restart == Restart::Glucose || solver.mode_switching {
+        println!(
+            "c restarts              : {:<12}   ({} blocked)",
+            solver.starts, solver.blocked_restarts
+        );
+    } else {
+        println!("c restarts              : {}", solver.starts);
+    }
+    println!(
+        "c conflicts             : {:<12}   ({:.0} /sec)",
+        solver.conflicts,
+        solver.conflicts as f64 / cpu_time
+    );
+    println!(
+        "c decisions             : {:<12}   ({:.0} /sec)",
+        solver.decisions,
+        solver.decisions as f64 / cpu_time
+    );
+    println!(
+        "c propagations          : {:<12}   ({:.0} /sec)",
+        solver.propagations,
+        solver.propagations as f64 / cpu_time
+    );
+    println!(
+        "c conflict literals     : {:<12}   ({:4.2} % deleted)",
+        solver.tot_literals,
+        (solver.max_literals - solver.tot_literals) as f64 * 100.0 / solver.max_literals as f64
+    );
+    println!(
+        "c binary minimization   : {:<12}   (literals removed)",
+        solver.bin_minimized_lits
+    );
+    println!(
+        "c chrono backtracks     : {:<12}   (non-chrono {})",
+        solver.chrono_backtrack, solver.non_chrono_backtrack
+    );
+    if solver.lcm {
+        println!(
+            "c learnt minimizations  : {:<12}   ({} of {} literals removed)",
+            solver.lcm_runs, solver.lcm_removed_lits, solver.lcm_tested_lits
+        );
+    }
+    if solver.probing {
+        println!(
+            "c probing rounds        : {:<12}   ({} probes, {} failed, {} units from both polarities)",
+            solver.probe_runs, solver.probed_lits, solver.failed_literals, solver.probe_units
+        );
+        println!("c hyper-binary clauses  : {}", solver.hbr_resolvents);
+    }
+    if solver.rephasing {
+        println!("c rephases              : {}", solver.rephases);
+        println!(
+            "c walks                 : {:<12}   ({} flips, {} unsat clauses at best)",
+            solver.walks, solver.walk_flips_total, solver.walk_best_unsat
+        );
+    }
+    if solver.mode_switching {
+        for (name, stats) in [
+            ("focused", &solver.focused_stats),
+            ("stable ", &solver.stable_stats),
+        ] {
+            println!(
+                "c {} conflicts     : {:<12}   ({} restarts, {} decisions, {} propagations)",
+                name, stats.conflicts, stats.starts, stats.decisions, stats.propagations
+            );
+        }
+    }
+    println!("c CPU time              : {:.3} s", cpu_time);
+}
+
+/// The model as DIMACS literals, e.g. "1 -2 3 0".
+fn model_line(solver: &Solver) -> String {
+    let mut line = String::new();
+    for v in 0..solver.num_vars() {
+        let lit = Lit::new(Var::from(v), false);
+        if lit.value(&solver.model) != LBool::Undefined {
+            let sign = if lit.value(&solver.model) == LBool::True {
+                ""
+            } else {
+                "-"
+            };
+            line += &format!("{}{} ", sign, v + 1);
+        }
+    }
+    line + "0"
+}
+
+/// The assumptions responsible for unsatisfiability, i.e. the negated final
+/// conflict, as DIMACS literals.
+fn failed_assumptions_line(solver: &Solver) -> String {
+    let mut line = String::new();
+    for &p in solver.conflict.iter() {
+        let q = !p;
+        let sign = if q.sign() { "-" } else { "" };
+        line += &format!("{}{} ", sign, q.var().into_usize() + 1);
+    }
+    line + "0"
+}
 
+fn main() {
     ctrlc::set_handler(interrupt).expect("Error setting Ctrl-C handler");
 
     // Interact with the user through the CLI
     let args = Args::parse();
-    println!("{}", args.input_file);
+    let start = Instant::now();
+
+    let mut solver = SimpSolver::with_solver(
+        Solver::new()
+            .with_branching(args.branching)
+            .with_stable_branching(args.stable_branching),
+    );
+    solver.verbosity = args.verb as i32;
+    solver.restart = args.restart;
+    solver.mode_switching = args.mode_switching;
+    solver.target_phase = args.target_phase;
+    solver.rephasing = args.rephase;
+    solver.walk_scoring = args.walk;
+    solver.lcm = args.lcm;
+    solver.probing = args.probe;
+    solver.probe_budget = args.probe_budget;
+    solver.chrono = args.chrono;
+    solver.conf_to_chrono = args.conf_to_chrono;
+    solver.use_simplification = args.pre;
+    solver.use_elim = args.elim;
+    solver.use_equiv = args.equiv;
+    solver.grow = args.grow;
+    solver.clause_lim = args.cl_lim;
+    solver.subsumption_lim = args.sub_lim;
+
+    let file = File::open(&args.input_file).expect("Failed to open the input file.");
+    solver.load_dimacs(BufReader::new(file));
+    if solver.verbosity > 0 {
+        println!("c Number of variables:  {}", solver.num_vars());
+        println!("c Number of clauses:    {}", solver.clauses.len());
+        println!(
+            "c Parse time:           {:.2} s",
+            start.elapsed().as_secs_f64()
+        );
+    }
+
+    let assumptions = args.assumptions.map(|path| {
+        let file = File::open(path).expect("Failed to open the assumptions file.");
+        solver.load_assumptions(BufReader::new(file))
+    });
+    // Assumed variables must survive the preprocessing:
+    for p in assumptions.iter().flatten() {
+        solver.set_frozen(p.var(), true);
+    }
+
+    let parsed_time = start.elapsed().as_secs_f64();
+    solver.eliminate(true);
+    if solver.verbosity > 0 {
+        println!(
+            "c Simplification time:  {:.2} s",
+            start.elapsed().as_secs_f64() - parsed_time
+        );
+    }
+
+    if let Some(path) = args.dimacs {
+        if solver.verbosity > 0 {
+            println!("c Writing the simplified formula to {}", path);
+        }
+        let file = File::create(path).expect("Failed to create the DIMACS file.");
+        solver
+            .write_dimacs(BufWriter::new(file), &assumptions.unwrap_or_default())
+            .expect("Failed to write the DIMACS file.");
+        if solver.verbosity > 0 {
+            print_stats(&solver, start);
+        }
+        process::exit(0);
+    }
+
+    let ret = if !solver.ok {
+        LBool::False
+    } else {
+        solver.solve_with(&assumptions.unwrap_or_default())
+    };
+    if solver.verbosity > 0 {
+        print_stats(&solver, start);
+    }
+
+    let mut res = args
+        .results_output_file
+        .map(|path| File::create(path).expect("Failed to create the results file."));
+    match ret {
+        LBool::True => {
+            println!("s SATISFIABLE");
+            println!("v {}", model_line(&solver));
+            if let Some(res) = res.as_mut() {
+                writeln!(res, "SAT\n{}", model_line(&solver)).expect("Failed to write results.");
+            }
+            process::exit(10);
+        }
+        LBool::False => {
+            println!("s UNSATISFIABLE");
+            if !solver.conflict.is_empty() {
+                println!("c Failed assumptions: {}", failed_assumptions_line(&solver));
+            }
+            if let Some(res) = res.as_mut() {
+                writeln!(res, "UNSAT").expect("Failed to write results.");
+            }
+            process::exit(20);
+        }
+        LBool::Undefined => {
+            println!("s UNKNOWN");
+            if let Some(res) = res.as_mut() {
+                writeln!(res, "INDET").expect("Failed to write results.");
+            }
+            process::exit(0);
+        }
+    }
 }
@@ -0,0 +1,101 @@
+use super::{chb::Chb, heap::Heap, lrb::Lrb, vsids::Vsids};
+
+/// The branching heuristics that can be selected at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Branching {
+    Lrb,
+    Chb,
+    Vsids,
+}
+
+impl Default for Branching {
+    /// The heuristic enabled through the cargo features. The non-default
+    /// `bh_vsids` and `bh_chb` take precedence over `bh_lrb`.
+    fn default() -> Self {
+        if cfg!(feature = "bh_vsids") {
+            Branching::Vsids
+        } else if cfg!(feature = "bh_chb") {
+            Branching::Chb
+        } else {
+            Branching::Lrb
+        }
+    }
+}
+
+impl Branching {
+    pub fn heuristic(self) -> Box<dyn BranchingHeuristic> {
+        match self {
+            Branching::Lrb => Box::new(Lrb::default()),
+            Branching::Chb => Box::new(Chb::default()),
+            Branching::Vsids => Box::new(Vsids::default()),
+        }
+    }
+}
+
+/// Hooks through which the solver lets a branching heuristic maintain the
+/// variable activities. The activities and the order heap belong to the
+/// solver; a heuristic that changes the activity of a variable in the heap
+/// must restore the heap order for it.
+pub trait BranchingHeuristic {
+    /// Allocates the state of a new variable.
+    fn new_var(&mut self);
+
+    /// Called when `v` is assigned.
+    fn on_assign(
+        &mut self,
+        _v: usize,
+        _conflicts: u64,
+        _activity: &mut [f64],
+        _order_heap: &mut Heap,
+    ) {
+    }
+
+    /// Called when `v` is unassigned by backtracking.
+    fn on_unassign(
+        &mut self,
+        _v: usize,
+        _conflicts: u64,
+        _activity: &mut [f64],
+        _order_heap: &mut Heap,
+    ) {
+    }
+
+    /// Called for every variable that conflict analysis resolves on or adds
+    /// to the learnt clause.
+    fn on_conflict_var(
+        &mut self,
+        _v: usize,
+        _conflicts: u64,
+        _activity: &mut [f64],
+        _order_heap: &mut Heap,
+    ) {
+    }
+
+    /// Whether conflict analysis should report the variables that only occur
+    /// in the reasons of the learnt clause through `on_reason_var`.
+    fn wants_reason_vars(&self) -> bool {
+        false
+    }
+
+    /// Called for every variable in the reasons of the learnt clause that is
+    /// not in the clause itself.
+    fn on_reason_var(&mut self, _v: usize) {}
+
+    /// Called at the end of every propagation, `conflict` telling whether it
+    /// ended in a conflict.
+    fn on_propagated(
+        &mut self,
+        _conflict: bool,
+        _conflicts: u64,
+        _activity: &mut [f64],
+        _order_heap: &mut Heap,
+    ) {
+    }
+
+    /// Called once per conflict, before it is analyzed.
+    fn on_conflict(&mut self) {}
+
+    /// Called before the top of the non-empty order heap is taken as the next
+    /// decision variable.
+    fn before_pick(&mut self, _conflicts: u64, _activity: &mut [f64], _order_heap: &mut Heap) {}
+}
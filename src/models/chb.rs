@@ -0,0 +1,23 @@
+/// State of the Conflict History-Based branching heuristic.
+pub struct Chb {
+    pub step_size: f64,
+    pub step_size_dec: f64,
+    pub min_step_size: f64,
+    pub reward_multiplier: f64, // Scales the reward of assignments that did not lead to a conflict.
+
+    pub last_conflict: Vec<u64>, // Conflict count when the variable last participated in a conflict.
+    pub assigned: Vec<usize>,    // Variables assigned since the last propagation ended.
+}
+
+impl Default for Chb {
+    fn default() -> Self {
+        Self {
+            step_size: 0.40,
+            step_size_dec: 0.000001,
+            min_step_size: 0.06,
+            reward_multiplier: 0.9,
+            last_conflict: vec![],
+            assigned: vec![],
+        }
+    }
+}
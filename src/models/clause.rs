@@ -1,38 +1,262 @@
-use std::{ops::Index, vec};
+use std::ops::{Index, IndexMut};
 
 use super::lit::Lit;
 
+/// Reference to a clause in the clause allocator; the offset of its header in
+/// the allocator's memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CRef(pub u32);
+
+impl CRef {
+    pub const UNDEF: CRef = CRef(u32::MAX);
+
+    pub fn into_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
+// Layout of the header word: mark (2 bits), learnt (1 bit), has_extra (1 bit),
+// reloced (1 bit), simplified (1 bit) and size (26 bits).
+const MARK_MASK: u32 = 0b11;
+const LEARNT_BIT: u32 = 1 << 2;
+const HAS_EXTRA_BIT: u32 = 1 << 3;
+const RELOCED_BIT: u32 = 1 << 4;
+const SIMPLIFIED_BIT: u32 = 1 << 5;
+const SIZE_SHIFT: u32 = 6;
+
+// Learnt clauses carry extra words after the literals: the activity and, with
+// LBD-based clause deletion, the LBD and the conflict count when the clause was
+// last used. Original clauses may carry their 64-bit abstraction instead.
+#[cfg(feature = "lbd_based_clause_deletion")]
+const EXTRA_WORDS: usize = 3;
+#[cfg(not(feature = "lbd_based_clause_deletion"))]
+const EXTRA_WORDS: usize = 2;
+
+// Marks of learnt clauses, giving the tier they belong to. Mark 1 is used for
+// deleted clauses.
+pub const LOCAL: u32 = 0;
+pub const TIER2: u32 = 2;
+pub const CORE: u32 = 3;
+
+/// A clause as laid out in the clause allocator: a header word, the literals
+/// and, if `has_extra` is set, the extra words after the literals.
+#[repr(transparent)]
 pub struct Clause {
-    // TODO: Consider using a header to group variables together
-    mark: u32,
-    learnt: u32,
-    has_extra: u32,
-    reloced: u32,
-    size: u32,
-
-    data: Vec<Lit>,
+    data: [u32],
 }
 
-pub static UNDEF_CLAUSE: Clause = Clause {
-    mark: 0,
-    learnt: 0,
-    has_extra: 0,
-    reloced: 0,
-    size: 0,
-    data: vec![],
-};
+impl Clause {
+    /// Number of 32-bit words needed to store a clause.
+    pub fn words(size: usize, has_extra: bool) -> usize {
+        1 + size + if has_extra { EXTRA_WORDS } else { 0 }
+    }
+
+    pub fn header(size: usize, learnt: bool, has_extra: bool) -> u32 {
+        assert!(size < 1 << (32 - SIZE_SHIFT), "Clause is too long.");
+        ((size as u32) << SIZE_SHIFT)
+            | if learnt { LEARNT_BIT } else { 0 }
+            | if has_extra { HAS_EXTRA_BIT } else { 0 }
+    }
+
+    /// Number of words occupied by the clause that starts at `words[0]`.
+    pub fn words_at(words: &[u32]) -> usize {
+        let header = words[0];
+        Clause::words((header >> SIZE_SHIFT) as usize, header & HAS_EXTRA_BIT != 0)
+    }
+
+    pub fn from_words(words: &[u32]) -> &Clause {
+        // SAFETY: 'Clause' is a transparent wrapper around '[u32]'.
+        unsafe { &*(words as *const [u32] as *const Clause) }
+    }
+
+    pub fn from_words_mut(words: &mut [u32]) -> &mut Clause {
+        // SAFETY: 'Clause' is a transparent wrapper around '[u32]'.
+        unsafe { &mut *(words as *mut [u32] as *mut Clause) }
+    }
+
+    pub fn size(&self) -> usize {
+        (self.data[0] >> SIZE_SHIFT) as usize
+    }
+
+    pub fn learnt(&self) -> bool {
+        self.data[0] & LEARNT_BIT != 0
+    }
+
+    pub fn has_extra(&self) -> bool {
+        self.data[0] & HAS_EXTRA_BIT != 0
+    }
+
+    pub fn reloced(&self) -> bool {
+        self.data[0] & RELOCED_BIT != 0
+    }
+
+    pub fn mark(&self) -> u32 {
+        self.data[0] & MARK_MASK
+    }
+
+    pub fn set_mark(&mut self, m: u32) {
+        self.data[0] = (self.data[0] & !MARK_MASK) | (m & MARK_MASK);
+    }
+
+    /// Whether the learnt clause was already minimized by vivification.
+    pub fn simplified(&self) -> bool {
+        self.data[0] & SIMPLIFIED_BIT != 0
+    }
+
+    pub fn set_simplified(&mut self, simplified: bool) {
+        if simplified {
+            self.data[0] |= SIMPLIFIED_BIT;
+        } else {
+            self.data[0] &= !SIMPLIFIED_BIT;
+        }
+    }
+
+    /// Removes the last `n` literals, moving the extra words after the
+    /// remaining ones. The freed words are only reclaimed by the garbage
+    /// collector.
+    pub fn shrink(&mut self, n: usize) {
+        let size = self.size();
+        assert!(n <= size);
+        if self.has_extra() {
+            self.data
+                .copy_within(1 + size..1 + size + EXTRA_WORDS, 1 + size - n);
+        }
+        self.data[0] =
+            (self.data[0] & ((1 << SIZE_SHIFT) - 1)) | (((size - n) as u32) << SIZE_SHIFT);
+    }
+
+    /// Forwarding address of a clause that was moved by the garbage collector.
+    pub fn relocation(&self) -> CRef {
+        CRef(self.data[1])
+    }
+
+    pub fn relocate(&mut self, c: CRef) {
+        self.data[0] |= RELOCED_BIT;
+        self.data[1] = c.0;
+    }
+
+    pub fn activity(&self) -> f32 {
+        assert!(self.has_extra());
+        f32::from_bits(self.data[1 + self.size()])
+    }
+
+    pub fn set_activity(&mut self, activity: f32) {
+        assert!(self.has_extra());
+        let size = self.size();
+        self.data[1 + size] = activity.to_bits();
+    }
+
+    /// Literal block distance of a learnt clause: the number of distinct
+    /// decision levels among its literals.
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    pub fn lbd(&self) -> u32 {
+        assert!(self.has_extra());
+        self.data[2 + self.size()]
+    }
+
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    pub fn set_lbd(&mut self, lbd: u32) {
+        assert!(self.has_extra());
+        let size = self.size();
+        self.data[2 + size] = lbd;
+    }
+
+    /// The (truncated) conflict count when the clause was last used in
+    /// conflict analysis.
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    pub fn touched(&self) -> u32 {
+        assert!(self.has_extra());
+        self.data[3 + self.size()]
+    }
+
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    pub fn set_touched(&mut self, touched: u32) {
+        assert!(self.has_extra());
+        let size = self.size();
+        self.data[3 + size] = touched;
+    }
+
+    /// A 64-bit summary of the variables of an original clause: a clause can
+    /// only subsume clauses whose abstraction includes its own.
+    pub fn abstraction(&self) -> u64 {
+        assert!(self.has_extra() && !self.learnt());
+        let size = self.size();
+        self.data[1 + size] as u64 | (self.data[2 + size] as u64) << 32
+    }
+
+    pub fn calc_abstraction(&mut self) {
+        assert!(self.has_extra() && !self.learnt());
+        let abstraction = self
+            .iter()
+            .fold(0u64, |abs, p| abs | 1 << (p.var().into_usize() & 63));
+        let size = self.size();
+        self.data[1 + size] = abstraction as u32;
+        self.data[2 + size] = (abstraction >> 32) as u32;
+    }
+
+    /// Checks if the clause subsumes `other`, or can be used to simplify it
+    /// by self-subsuming resolution. Returns `None` if neither is possible,
+    /// `Lit::UNDEF` if it subsumes `other`, or the literal `p` of the clause
+    /// such that `!p` can be removed from `other`.
+    pub fn subsumes(&self, other: &Clause) -> Option<Lit> {
+        if other.size() < self.size() || self.abstraction() & !other.abstraction() != 0 {
+            return None;
+        }
+
+        let mut ret = Lit::UNDEF;
+        for &p in self.iter() {
+            // Search for p or !p:
+            if other.iter().any(|&q| q == p) {
+                continue;
+            } else if ret == Lit::UNDEF && other.iter().any(|&q| q == !p) {
+                ret = p;
+            } else {
+                return None;
+            }
+        }
+        Some(ret)
+    }
+
+    /// The raw words of the clause, header included.
+    pub fn words_slice(&self) -> &[u32] {
+        &self.data
+    }
+
+    pub fn lits(&self) -> &[Lit] {
+        let words = &self.data[1..1 + self.size()];
+        // SAFETY: 'Lit' is a transparent wrapper around 'i32', which has the
+        // same layout as 'u32'.
+        unsafe { &*(words as *const [u32] as *const [Lit]) }
+    }
+
+    pub fn lits_mut(&mut self) -> &mut [Lit] {
+        let size = self.size();
+        let words = &mut self.data[1..1 + size];
+        // SAFETY: 'Lit' is a transparent wrapper around 'i32', which has the
+        // same layout as 'u32'.
+        unsafe { &mut *(words as *mut [u32] as *mut [Lit]) }
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.lits_mut().swap(a, b);
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Lit> {
+        self.lits().iter()
+    }
+}
 
 impl Index<usize> for Clause {
     type Output = Lit;
 
     fn index(&self, index: usize) -> &Self::Output {
-        &self.data[index]
+        &self.lits()[index]
     }
 }
 
-impl Clause {
-    pub fn iter(&self) -> std::slice::Iter<Lit> {
-        self.data.iter()
+impl IndexMut<usize> for Clause {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.lits_mut()[index]
     }
 }
 
@@ -41,6 +265,6 @@ impl<'a> IntoIterator for &'a Clause {
     type IntoIter = std::slice::Iter<'a, Lit>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.data.iter()
+        self.lits().iter()
     }
 }
@@ -0,0 +1,271 @@
+use std::ops::{Index, IndexMut};
+
+use super::{
+    clause::{CRef, Clause},
+    lit::Lit,
+};
+
+/// Region allocator for clauses. Clauses are stored back to back in a single
+/// buffer of 32-bit words and referred to by the offset of their header.
+#[derive(Default)]
+pub struct ClauseAllocator {
+    memory: Vec<u32>,
+    wasted: usize,                // Number of words occupied by freed clauses.
+    pub extra_clause_field: bool, // Original clauses carry their abstraction, for subsumption.
+}
+
+impl ClauseAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        ClauseAllocator {
+            memory: Vec::with_capacity(capacity),
+            wasted: 0,
+            extra_clause_field: false,
+        }
+    }
+
+    pub fn alloc(&mut self, ps: &[Lit], learnt: bool) -> CRef {
+        let has_extra = learnt || self.extra_clause_field;
+        let cr = CRef(self.memory.len() as u32);
+        self.memory.reserve(Clause::words(ps.len(), has_extra));
+        self.memory
+            .push(Clause::header(ps.len(), learnt, has_extra));
+        self.memory
+            .extend(ps.iter().map(|&p| p.into_usize() as u32));
+        self.memory
+            .resize(cr.into_usize() + Clause::words(ps.len(), has_extra), 0);
+        if has_extra && !learnt {
+            self[cr].calc_abstraction();
+        }
+        cr
+    }
+
+    /// The memory of a freed clause is only reclaimed by the garbage collector.
+    pub fn free(&mut self, cr: CRef) {
+        self.wasted += Clause::words_at(&self.memory[cr.into_usize()..]);
+    }
+
+    /// Removes the last `n` literals of the clause `cr`. The words it no
+    /// longer uses count as wasted.
+    pub fn shrink(&mut self, cr: CRef, n: usize) {
+        self[cr].shrink(n);
+        self.wasted += n;
+    }
+
+    /// Removes the literal `p` from the clause `cr`, keeping the order of the
+    /// other literals.
+    pub fn strengthen(&mut self, cr: CRef, p: Lit) {
+        let c = &mut self[cr];
+        let i = c.iter().position(|&q| q == p).unwrap();
+        let size = c.size();
+        c.lits_mut().copy_within(i + 1..size, i);
+        self.shrink(cr, 1);
+        if self[cr].has_extra() && !self[cr].learnt() {
+            self[cr].calc_abstraction();
+        }
+    }
+
+    /// Moves the clause `cr` into `to` unless it was moved already, and
+    /// updates `cr` to its new location.
+    pub fn reloc(&mut self, cr: &mut CRef, to: &mut ClauseAllocator) {
+        let c = &mut self[*cr];
+        if c.reloced() {
+            *cr = c.relocation();
+            return;
+        }
+
+        let new_cr = CRef(to.memory.len() as u32);
+        to.memory.extend_from_slice(c.words_slice());
+        c.relocate(new_cr);
+        *cr = new_cr;
+    }
+
+    /// Number of words in use, including those of deleted clauses.
+    pub fn size(&self) -> usize {
+        self.memory.len()
+    }
+
+    pub fn wasted(&self) -> usize {
+        self.wasted
+    }
+}
+
+impl Index<CRef> for ClauseAllocator {
+    type Output = Clause;
+
+    fn index(&self, cr: CRef) -> &Self::Output {
+        let start = cr.into_usize();
+        let end = start + Clause::words_at(&self.memory[start..]);
+        Clause::from_words(&self.memory[start..end])
+    }
+}
+
+impl IndexMut<CRef> for ClauseAllocator {
+    fn index_mut(&mut self, cr: CRef) -> &mut Self::Output {
+        let start = cr.into_usize();
+        let end = start + Clause::words_at(&self.memory[start..]);
+        Clause::from_words_mut(&mut self.memory[start..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::var::Var;
+
+    use super::*;
+
+    fn lits(vars: &[(i32, bool)]) -> Vec<Lit> {
+        vars.iter()
+            .map(|&(v, sign)| Lit::new(Var::from(v), sign))
+            .collect()
+    }
+
+    #[test]
+    fn test_alloc_and_read_back() {
+        let mut ca = ClauseAllocator::new();
+        let ps = lits(&[(0, false), (3, true), (7, false)]);
+        let cr = ca.alloc(&ps, false);
+
+        let c = &ca[cr];
+        assert_eq!(c.size(), 3);
+        assert!(!c.learnt());
+        assert!(!c.has_extra());
+        assert!(!c.reloced());
+        assert_eq!(c.mark(), 0);
+        assert_eq!(c.lits(), &ps[..]);
+    }
+
+    #[test]
+    fn test_clauses_are_packed_back_to_back() {
+        let mut ca = ClauseAllocator::new();
+        let cr1 = ca.alloc(&lits(&[(0, false), (1, false)]), false);
+        let cr2 = ca.alloc(&lits(&[(2, true), (3, true), (4, true)]), true);
+        let cr3 = ca.alloc(&lits(&[(5, false), (6, false)]), false);
+
+        assert_eq!(cr1, CRef(0));
+        assert_eq!(cr2, CRef(3));
+        // Learnt clauses carry extra words after their literals.
+        let learnt_words = Clause::words(3, true) as u32;
+        assert_eq!(cr3, CRef(3 + learnt_words));
+        assert_eq!(ca.size(), 6 + learnt_words as usize);
+
+        assert!(ca[cr2].learnt());
+        assert!(ca[cr2].has_extra());
+        assert_eq!(
+            ca[cr2].lits(),
+            &lits(&[(2, true), (3, true), (4, true)])[..]
+        );
+        assert_eq!(ca[cr3][1], Lit::new(Var::from(6), false));
+    }
+
+    #[test]
+    fn test_mutate_clause() {
+        let mut ca = ClauseAllocator::new();
+        let cr = ca.alloc(&lits(&[(0, false), (1, false), (2, false)]), false);
+
+        ca[cr].swap(0, 2);
+        ca[cr].set_mark(1);
+        assert_eq!(ca[cr].mark(), 1);
+        assert_eq!(ca[cr].size(), 3);
+        assert_eq!(ca[cr][0], Lit::new(Var::from(2), false));
+        assert_eq!(ca[cr][2], Lit::new(Var::from(0), false));
+    }
+
+    #[test]
+    fn test_shrink_keeps_extra_words() {
+        let mut ca = ClauseAllocator::new();
+        let cr = ca.alloc(&lits(&[(0, false), (1, false), (2, false)]), true);
+        ca[cr].set_activity(2.5);
+        #[cfg(feature = "lbd_based_clause_deletion")]
+        ca[cr].set_lbd(3);
+        ca[cr].set_simplified(true);
+        ca[cr].set_mark(2);
+
+        ca.shrink(cr, 1);
+        assert_eq!(ca.wasted(), 1);
+        assert_eq!(ca[cr].size(), 2);
+        assert_eq!(ca[cr].lits(), &lits(&[(0, false), (1, false)])[..]);
+        assert_eq!(ca[cr].activity(), 2.5);
+        #[cfg(feature = "lbd_based_clause_deletion")]
+        assert_eq!(ca[cr].lbd(), 3);
+        assert!(ca[cr].simplified() && ca[cr].learnt());
+        assert_eq!(ca[cr].mark(), 2);
+    }
+
+    #[test]
+    fn test_free_counts_wasted_words() {
+        let mut ca = ClauseAllocator::new();
+        let cr1 = ca.alloc(&lits(&[(0, false), (1, false)]), false);
+        let cr2 = ca.alloc(&lits(&[(2, false), (3, false)]), true);
+
+        ca.free(cr1);
+        assert_eq!(ca.wasted(), 3);
+        ca.free(cr2);
+        assert_eq!(ca.wasted(), 3 + Clause::words(2, true));
+        assert_eq!(ca.size(), ca.wasted());
+    }
+
+    #[test]
+    fn test_reloc_moves_clause_once() {
+        let mut ca = ClauseAllocator::new();
+        let ps1 = lits(&[(0, false), (1, true)]);
+        let ps2 = lits(&[(2, false), (3, true), (4, false)]);
+        let cr1 = ca.alloc(&ps1, false);
+        let cr2 = ca.alloc(&ps2, true);
+        ca[cr2].set_activity(1.5);
+        #[cfg(feature = "lbd_based_clause_deletion")]
+        ca[cr2].set_lbd(2);
+        ca.free(cr1);
+
+        let mut to = ClauseAllocator::with_capacity(ca.size() - ca.wasted());
+        let mut moved = cr2;
+        ca.reloc(&mut moved, &mut to);
+        assert_eq!(moved, CRef(0));
+        assert!(ca[cr2].reloced());
+        assert_eq!(to[moved].lits(), &ps2[..]);
+        assert!(to[moved].learnt());
+        assert_eq!(to[moved].activity(), 1.5);
+        #[cfg(feature = "lbd_based_clause_deletion")]
+        assert_eq!(to[moved].lbd(), 2);
+
+        // A second reference to the same clause follows the forwarding address.
+        let mut again = cr2;
+        ca.reloc(&mut again, &mut to);
+        assert_eq!(again, moved);
+        assert_eq!(to.size(), Clause::words(3, true));
+    }
+
+    #[test]
+    fn test_abstraction_and_subsumption() {
+        let mut ca = ClauseAllocator::new();
+        ca.extra_clause_field = true;
+        let c1 = ca.alloc(&lits(&[(0, false), (1, true)]), false);
+        let c2 = ca.alloc(&lits(&[(0, false), (1, true), (2, false)]), false);
+        let c3 = ca.alloc(&lits(&[(0, false), (1, false), (2, false)]), false);
+        let c4 = ca.alloc(&lits(&[(0, true), (1, false), (64, false)]), false);
+        assert_eq!(ca[c2].abstraction(), 0b111);
+        assert_eq!(ca[c4].abstraction(), 0b11);
+
+        assert_eq!(ca[c1].subsumes(&ca[c2]), Some(Lit::UNDEF));
+        assert_eq!(ca[c2].subsumes(&ca[c1]), None);
+        // Self-subsuming resolution removes x1 from the third clause:
+        assert_eq!(ca[c1].subsumes(&ca[c3]), Some(Lit::new(Var::from(1), true)));
+        // Both literals are negated:
+        assert_eq!(ca[c1].subsumes(&ca[c4]), None);
+    }
+
+    #[test]
+    fn test_strengthen() {
+        let mut ca = ClauseAllocator::new();
+        ca.extra_clause_field = true;
+        let cr = ca.alloc(&lits(&[(0, false), (1, true), (2, false)]), false);
+
+        ca.strengthen(cr, Lit::new(Var::from(1), true));
+        assert_eq!(ca[cr].lits(), &lits(&[(0, false), (2, false)])[..]);
+        assert_eq!(ca[cr].abstraction(), 0b101);
+        assert_eq!(ca.wasted(), 1);
+    }
+}
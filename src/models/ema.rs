@@ -0,0 +1,61 @@
+/// Exponential moving average with bias correction, so that the first
+/// values are not biased towards the initial zero.
+#[derive(Clone, Copy, Debug)]
+pub struct Ema {
+    biased: f64, // The moving average, started at 0.
+    exp: f64,    // (1 - alpha)^n after n updates, for the bias correction.
+    alpha: f64,  // Weight of a new value.
+}
+
+impl Ema {
+    pub fn new(alpha: f64) -> Self {
+        Ema {
+            biased: 0.0,
+            exp: 1.0,
+            alpha,
+        }
+    }
+
+    pub fn update(&mut self, x: f64) {
+        self.biased += self.alpha * (x - self.biased);
+        self.exp *= 1.0 - self.alpha;
+    }
+
+    /// The current average, 0 before the first update.
+    pub fn value(&self) -> f64 {
+        if self.exp < 1.0 {
+            self.biased / (1.0 - self.exp)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_value_is_unbiased() {
+        let mut ema = Ema::new(0.01);
+        assert_eq!(ema.value(), 0.0);
+        ema.update(5.0);
+        assert!((ema.value() - 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_fast_average_follows_recent_values() {
+        let mut fast = Ema::new(0.5);
+        let mut slow = Ema::new(0.001);
+        for _ in 0..100 {
+            fast.update(2.0);
+            slow.update(2.0);
+        }
+        for _ in 0..10 {
+            fast.update(10.0);
+            slow.update(10.0);
+        }
+        assert!(fast.value() > 9.9);
+        assert!(slow.value() < 3.0);
+    }
+}
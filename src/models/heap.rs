@@ -0,0 +1,209 @@
+use std::ops::Index;
+
+/// Binary heap of variables ordered by activity, highest first. Supports
+/// membership tests and updating the position of a variable after its
+/// activity changed.
+///
+/// Following MiniSat, `decrease` and `increase` refer to the position in the
+/// heap order: `decrease` moves a variable towards the top (its activity went
+/// up) and `increase` towards the bottom (its activity went down).
+#[derive(Default)]
+pub struct Heap {
+    heap: Vec<usize>,    // Heap of variables.
+    indices: Vec<usize>, // Each variable's position in 'heap', or 'NOT_IN_HEAP'.
+}
+
+const NOT_IN_HEAP: usize = usize::MAX;
+
+fn left(i: usize) -> usize {
+    i * 2 + 1
+}
+
+fn right(i: usize) -> usize {
+    (i + 1) * 2
+}
+
+fn parent(i: usize) -> usize {
+    (i - 1) >> 1
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn in_heap(&self, v: usize) -> bool {
+        v < self.indices.len() && self.indices[v] != NOT_IN_HEAP
+    }
+
+    fn percolate_up(&mut self, mut i: usize, act: &[f64]) {
+        let x = self.heap[i];
+        while i != 0 && act[x] > act[self.heap[parent(i)]] {
+            let p = parent(i);
+            self.heap[i] = self.heap[p];
+            self.indices[self.heap[i]] = i;
+            i = p;
+        }
+        self.heap[i] = x;
+        self.indices[x] = i;
+    }
+
+    fn percolate_down(&mut self, mut i: usize, act: &[f64]) {
+        let x = self.heap[i];
+        while left(i) < self.heap.len() {
+            let child = if right(i) < self.heap.len()
+                && act[self.heap[right(i)]] > act[self.heap[left(i)]]
+            {
+                right(i)
+            } else {
+                left(i)
+            };
+            if act[self.heap[child]] <= act[x] {
+                break;
+            }
+            self.heap[i] = self.heap[child];
+            self.indices[self.heap[i]] = i;
+            i = child;
+        }
+        self.heap[i] = x;
+        self.indices[x] = i;
+    }
+
+    /// Restores the order after the activity of `v` went up.
+    pub fn decrease(&mut self, v: usize, act: &[f64]) {
+        assert!(self.in_heap(v));
+        self.percolate_up(self.indices[v], act);
+    }
+
+    /// Restores the order after the activity of `v` went down.
+    pub fn increase(&mut self, v: usize, act: &[f64]) {
+        assert!(self.in_heap(v));
+        self.percolate_down(self.indices[v], act);
+    }
+
+    pub fn insert(&mut self, v: usize, act: &[f64]) {
+        if self.indices.len() <= v {
+            self.indices.resize(v + 1, NOT_IN_HEAP);
+        }
+        assert!(!self.in_heap(v));
+
+        self.indices[v] = self.heap.len();
+        self.heap.push(v);
+        self.percolate_up(self.indices[v], act);
+    }
+
+    /// Removes and returns the variable with the highest activity.
+    pub fn remove_min(&mut self, act: &[f64]) -> usize {
+        let x = self.heap.swap_remove(0);
+        self.indices[x] = NOT_IN_HEAP;
+        if !self.heap.is_empty() {
+            self.indices[self.heap[0]] = 0;
+            self.percolate_down(0, act);
+        }
+        x
+    }
+
+    /// Rebuilds the heap from scratch, using the variables in `ns`.
+    pub fn build(&mut self, ns: &[usize], act: &[f64]) {
+        for &x in self.heap.iter() {
+            self.indices[x] = NOT_IN_HEAP;
+        }
+        self.heap.clear();
+
+        for (i, &x) in ns.iter().enumerate() {
+            if self.indices.len() <= x {
+                self.indices.resize(x + 1, NOT_IN_HEAP);
+            }
+            self.indices[x] = i;
+            self.heap.push(x);
+        }
+
+        for i in (0..self.heap.len() / 2).rev() {
+            self.percolate_down(i, act);
+        }
+    }
+}
+
+impl Index<usize> for Heap {
+    type Output = usize;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.heap[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn drain(heap: &mut Heap, act: &[f64]) -> Vec<usize> {
+        let mut order = vec![];
+        while !heap.is_empty() {
+            order.push(heap.remove_min(act));
+        }
+        order
+    }
+
+    #[test]
+    fn test_remove_min_returns_highest_activity_first() {
+        let act = [0.5, 3.0, 1.0, 2.5, 0.0];
+        let mut heap = Heap::new();
+        for v in 0..act.len() {
+            heap.insert(v, &act);
+        }
+        assert_eq!(heap.len(), 5);
+        assert_eq!(heap[0], 1);
+        assert_eq!(drain(&mut heap, &act), vec![1, 3, 2, 0, 4]);
+        assert!(!heap.in_heap(1));
+    }
+
+    #[test]
+    fn test_update_after_activity_change() {
+        let mut act = vec![4.0, 3.0, 2.0, 1.0];
+        let mut heap = Heap::new();
+        for v in 0..act.len() {
+            heap.insert(v, &act);
+        }
+
+        act[3] = 10.0;
+        heap.decrease(3, &act);
+        assert_eq!(heap[0], 3);
+
+        act[3] = 0.5;
+        heap.increase(3, &act);
+        act[0] = 0.0;
+        heap.increase(0, &act);
+        assert_eq!(drain(&mut heap, &act), vec![1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn test_in_heap() {
+        let act = [1.0, 2.0, 3.0];
+        let mut heap = Heap::new();
+        assert!(!heap.in_heap(0));
+        heap.insert(2, &act);
+        assert!(heap.in_heap(2));
+        assert!(!heap.in_heap(0));
+        heap.remove_min(&act);
+        assert!(!heap.in_heap(2));
+    }
+
+    #[test]
+    fn test_build() {
+        let act = [1.0, 5.0, 3.0, 4.0, 2.0];
+        let mut heap = Heap::new();
+        heap.insert(0, &act);
+        heap.build(&[4, 3, 2, 1], &act);
+
+        assert!(!heap.in_heap(0));
+        assert_eq!(drain(&mut heap, &act), vec![1, 3, 2, 4]);
+    }
+}
@@ -1,6 +1,6 @@
 /// Lifted Boolean. In other words, boolean that has undefined value besides
 /// true and false.
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LBool {
     True = 0,
     False = 1,
@@ -1,14 +1,19 @@
 use super::{lbool::LBool, var::Var};
 use std::ops::Not;
 
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
 pub struct Lit {
     value: i32,
 }
 
 // Note: Sign is in the LSB and the var in the MSB
 impl Lit {
+    /// Placeholder for "no literal"; its negation is not a valid literal either.
+    pub const UNDEF: Lit = Lit { value: -2 };
+
     pub fn new(var: Var, sign: bool) -> Lit {
-        assert!(var >= 0 && var <= 1);
+        assert!(var >= 0);
         let value = (var.id << 1) | (sign as i32);
         Lit { value }
     }
@@ -21,17 +26,14 @@ impl Lit {
         Var::from(self.value >> 1)
     }
 
-    pub fn value(&self, values: &Vec<LBool>) -> LBool {
+    pub fn value(&self, values: &[LBool]) -> LBool {
         let value = self.var().value(values);
         LBool::from((value as i32) ^ (self.sign() as i32))
     }
-}
-
-impl Copy for Lit {}
 
-impl Clone for Lit {
-    fn clone(&self) -> Self {
-        *self
+    /// Index of the literal in literal-indexed tables such as watch lists.
+    pub fn into_usize(self) -> usize {
+        self.value as usize
     }
 }
 
@@ -41,21 +43,9 @@ impl From<i32> for Lit {
     }
 }
 
-impl Default for Lit {
-    fn default() -> Self {
-        Self { value: 0 }
-    }
-}
-
-impl PartialEq for Lit {
-    fn eq(&self, other: &Self) -> bool {
-        self.value == other.value
-    }
-}
-
-impl PartialOrd for Lit {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.value.cmp(&other.value))
+impl From<Lit> for usize {
+    fn from(lit: Lit) -> Self {
+        lit.into_usize()
     }
 }
 
@@ -0,0 +1,29 @@
+/// State of the Learning-Rate Branching heuristic.
+pub struct Lrb {
+    pub step_size: f64,
+    pub step_size_dec: f64,
+    pub min_step_size: f64,
+    pub almost_conflict: bool, // Also reward variables in the reasons of the learnt clause.
+    pub anti_exploration: bool, // Decay the activity of unassigned variables for the conflicts they miss.
+
+    pub picked: Vec<u64>,     // Conflict count when the variable was assigned.
+    pub conflicted: Vec<u64>, // Conflicts the variable participated in since it was assigned.
+    pub almost_conflicted: Vec<u64>, // Same, for the reasons of the learnt clauses.
+    pub canceled: Vec<u64>,   // Conflict count when the variable was unassigned.
+}
+
+impl Default for Lrb {
+    fn default() -> Self {
+        Self {
+            step_size: 0.40,
+            step_size_dec: 0.000001,
+            min_step_size: 0.06,
+            almost_conflict: cfg!(feature = "almost_conflict"),
+            anti_exploration: cfg!(feature = "anti_exploration"),
+            picked: vec![],
+            conflicted: vec![],
+            almost_conflicted: vec![],
+            canceled: vec![],
+        }
+    }
+}
@@ -1,7 +1,21 @@
+pub mod branching_heuristic;
+pub mod chb;
 pub mod clause;
+pub mod clause_allocator;
+pub mod ema;
+pub mod heap;
 pub mod lbool;
 pub mod lit;
+pub mod lrb;
+pub mod mode;
+pub mod occ_lists;
+pub mod reconstruction_stack;
+pub mod rephase;
+pub mod restart;
+pub mod simp_solver;
 pub mod solver;
 pub mod var;
 pub mod var_data;
+pub mod vsids;
+pub mod walker;
 pub mod watcher;
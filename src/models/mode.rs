@@ -0,0 +1,17 @@
+/// The search modes alternated by mode switching.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Frequent Glucose restarts, to quickly focus on recent conflicts.
+    Focused,
+    /// Rare restarts, to complete the search of the current region.
+    Stable,
+}
+
+/// Statistics of the search spent in one mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ModeStats {
+    pub conflicts: u64,
+    pub decisions: u64,
+    pub propagations: u64,
+    pub starts: u64,
+}
@@ -0,0 +1,77 @@
+use std::ops::{Index, IndexMut};
+
+/// Occurrence lists indexed by `K` with support for lazy deletion: removing an
+/// element only smudges its list, which is cleaned on the next `clean` or
+/// `clean_all`.
+pub struct OccLists<K, T> {
+    occs: Vec<Vec<T>>,
+    dirty: Vec<bool>,
+    dirties: Vec<K>,
+}
+
+impl<K: Copy + Into<usize>, T> OccLists<K, T> {
+    pub fn new() -> Self {
+        OccLists {
+            occs: vec![],
+            dirty: vec![],
+            dirties: vec![],
+        }
+    }
+
+    /// Makes sure there is a (possibly empty) list for `idx`.
+    pub fn init(&mut self, idx: K) {
+        let size = idx.into() + 1;
+        if self.occs.len() < size {
+            self.occs.resize_with(size, Vec::new);
+            self.dirty.resize(size, false);
+        }
+    }
+
+    /// Marks the list of `idx` as containing deleted elements.
+    pub fn smudge(&mut self, idx: K) {
+        if !self.dirty[idx.into()] {
+            self.dirty[idx.into()] = true;
+            self.dirties.push(idx);
+        }
+    }
+
+    pub fn clean(&mut self, idx: K, deleted: impl Fn(&T) -> bool) {
+        self.occs[idx.into()].retain(|x| !deleted(x));
+        self.dirty[idx.into()] = false;
+    }
+
+    pub fn clean_all(&mut self, deleted: impl Fn(&T) -> bool) {
+        for i in 0..self.dirties.len() {
+            let idx = self.dirties[i];
+            // Dirties may contain duplicates so check here if a variable is already cleaned:
+            if self.dirty[idx.into()] {
+                self.clean(idx, &deleted);
+            }
+        }
+        self.dirties.clear();
+    }
+
+    pub fn lists_mut(&mut self) -> std::slice::IterMut<'_, Vec<T>> {
+        self.occs.iter_mut()
+    }
+}
+
+impl<K: Copy + Into<usize>, T> Default for OccLists<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Copy + Into<usize>, T> Index<K> for OccLists<K, T> {
+    type Output = Vec<T>;
+
+    fn index(&self, idx: K) -> &Self::Output {
+        &self.occs[idx.into()]
+    }
+}
+
+impl<K: Copy + Into<usize>, T> IndexMut<K> for OccLists<K, T> {
+    fn index_mut(&mut self, idx: K) -> &mut Self::Output {
+        &mut self.occs[idx.into()]
+    }
+}
@@ -0,0 +1,96 @@
+use super::{lbool::LBool, lit::Lit};
+
+/// The clauses removed by preprocessing, each with a witness: literals whose
+/// assignment satisfies the clause without falsifying any clause still in the
+/// solver or pushed before it. Replaying the stack backward extends a model of
+/// the simplified formula to one of the original formula.
+#[derive(Default)]
+pub struct ReconstructionStack {
+    lits: Vec<Lit>, // The witness and then the clause of each entry, one entry after the other.
+    entries: Vec<(usize, usize)>, // Where the witness and the clause of each entry start in 'lits'.
+}
+
+impl ReconstructionStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Records the removal of `clause`, which `witness` satisfies. A variable
+    /// only given a default value is pushed as a unit clause witnessed by
+    /// itself.
+    pub fn push(&mut self, witness: &[Lit], clause: &[Lit]) {
+        let start = self.lits.len();
+        self.lits.extend_from_slice(witness);
+        self.lits.extend_from_slice(clause);
+        self.entries.push((start, start + witness.len()));
+    }
+
+    /// Goes through the entries from the last to the first, and assigns the
+    /// witness of every clause the model does not satisfy.
+    pub fn extend(&self, model: &mut [LBool]) {
+        let mut end = self.lits.len();
+        for &(witness, clause) in self.entries.iter().rev() {
+            if !self.lits[clause..end]
+                .iter()
+                .any(|p| p.value(model) == LBool::True)
+            {
+                for &p in self.lits[witness..clause].iter() {
+                    model[p.var().into_usize()] = LBool::from(!p.sign());
+                }
+            }
+            end = witness;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::clause;
+
+    #[test]
+    fn test_extend_replays_backward() {
+        // x1 was eliminated keeping (1 2) and defaulting to false, then x2
+        // keeping (-2 -3) and defaulting to true.
+        let mut stack = ReconstructionStack::new();
+        stack.push(&clause(&[1]), &clause(&[1, 2]));
+        stack.push(&clause(&[-1]), &clause(&[-1]));
+        stack.push(&clause(&[-2]), &clause(&[-2, -3]));
+        stack.push(&clause(&[2]), &clause(&[2]));
+        assert_eq!(stack.len(), 4);
+
+        // x3 is false, so both variables keep their defaults:
+        let mut model = vec![LBool::Undefined, LBool::Undefined, LBool::False];
+        stack.extend(&mut model);
+        assert_eq!(model, vec![LBool::False, LBool::True, LBool::False]);
+
+        // With x3 true, x2 is false and x1 must be true:
+        let mut model = vec![LBool::Undefined, LBool::Undefined, LBool::True];
+        stack.extend(&mut model);
+        assert_eq!(model, vec![LBool::True, LBool::False, LBool::True]);
+    }
+
+    #[test]
+    fn test_extend_keeps_satisfying_models() {
+        let mut stack = ReconstructionStack::new();
+        assert!(stack.is_empty());
+        stack.push(&clause(&[1, -2]), &clause(&[1, 3]));
+
+        let mut model = vec![LBool::False, LBool::True, LBool::True];
+        stack.extend(&mut model);
+        assert_eq!(model, vec![LBool::False, LBool::True, LBool::True]);
+
+        // The whole witness is assigned:
+        let mut model = vec![LBool::False, LBool::True, LBool::False];
+        stack.extend(&mut model);
+        assert_eq!(model, vec![LBool::True, LBool::False, LBool::False]);
+    }
+}
@@ -0,0 +1,29 @@
+/// The ways of resetting the saved phases when rephasing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rephase {
+    /// The initial polarity of every variable.
+    Original,
+    /// The opposite of the initial polarity.
+    Inverted,
+    /// The assignment of the longest conflict-free trail since the last
+    /// rephasing.
+    Best,
+    /// A random polarity for every variable.
+    Random,
+    /// The best assignment found by local search from the saved phases.
+    Walk,
+}
+
+/// The order in which the rephasings are applied, repeating from
+/// `REPHASE_CYCLE_START` once the schedule is exhausted.
+pub const REPHASE_SCHEDULE: [Rephase; 8] = [
+    Rephase::Original,
+    Rephase::Inverted,
+    Rephase::Best,
+    Rephase::Walk,
+    Rephase::Random,
+    Rephase::Best,
+    Rephase::Walk,
+    Rephase::Original,
+];
+pub const REPHASE_CYCLE_START: usize = 2;
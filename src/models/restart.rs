@@ -0,0 +1,11 @@
+/// The restart policies that can be selected at runtime.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Restart {
+    /// Restart limits follow the Luby sequence.
+    #[default]
+    Luby,
+    /// Restart limits grow geometrically.
+    Geometric,
+    /// Restart when recent learnt clauses have a high LBD (Glucose).
+    Glucose,
+}
@@ -0,0 +1,56 @@
+use std::{
+    collections::VecDeque,
+    ops::{Deref, DerefMut},
+};
+
+use super::{clause::CRef, heap::Heap, lit::Lit, occ_lists::OccLists, solver::Solver, var::Var};
+
+/// A solver with a SatELite-style preprocessor, which substitutes equivalent
+/// literals, eliminates variables by clause distribution and removes subsumed
+/// clauses before the search. Everything else is delegated to the underlying
+/// `Solver`.
+pub struct SimpSolver {
+    pub solver: Solver,
+
+    pub grow: i32, // Allow a variable elimination step to grow by a number of clauses.                         (default 0)
+    pub clause_lim: i32, // Variables are not eliminated if it produces a resolvent with a length above this limit. (default 20)
+    pub subsumption_lim: i32, // Do not check subsumption against clauses larger than this. -1 means no limit. (default 1000)
+    pub use_elim: bool,       // Perform variable elimination.
+    pub use_equiv: bool, // Substitute equivalent literals by a representative before eliminating variables.
+
+    // Statistics:
+    pub merges: u64,
+    pub eliminated_vars: u64,
+    pub substituted_vars: u64,
+    pub subsumed_clauses: u64,
+    pub deleted_literals: u64,
+
+    // Solver state:
+    pub use_simplification: bool, // Cleared once the preprocessing is turned off for good.
+    pub frozen: Vec<bool>, // Frozen variables are not eliminated, e.g. those used in assumptions.
+    pub eliminated: Vec<bool>,
+    pub occurs: OccLists<Var, CRef>, // 'occurs[var]' is the list of original clauses containing 'var'. Only built during 'eliminate()'.
+    pub n_occ: Vec<i32>,             // The number of original clauses containing each literal.
+    pub elim_heap: Heap,             // The variables to try eliminating, cheapest first.
+    pub elim_cost: Vec<f64>, // The negated number of resolutions needed to eliminate each variable, ordering 'elim_heap'.
+    pub resolvent: Vec<Lit>,
+    pub subsumption_queue: VecDeque<CRef>, // The clauses to check for backward subsumption.
+    pub touched: Vec<bool>, // The variables whose clauses were added to since the last subsumption check.
+    pub n_touched: usize,
+    pub bwdsub_assigns: usize, // The top-level assignments already used for subsumption.
+    pub bwdsub_tmpunit: CRef,  // A unit clause holding the top-level assignment being checked.
+}
+
+impl Deref for SimpSolver {
+    type Target = Solver;
+
+    fn deref(&self) -> &Self::Target {
+        &self.solver
+    }
+}
+
+impl DerefMut for SimpSolver {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.solver
+    }
+}
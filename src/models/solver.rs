@@ -1,29 +1,43 @@
-use super::{lbool::LBool, lit::Lit, var_data::VarData};
-
-pub struct Solver<'a> {
+use super::{
+    branching_heuristic::BranchingHeuristic,
+    clause::CRef,
+    clause_allocator::ClauseAllocator,
+    ema::Ema,
+    heap::Heap,
+    lbool::LBool,
+    lit::Lit,
+    mode::{Mode, ModeStats},
+    occ_lists::OccLists,
+    reconstruction_stack::ReconstructionStack,
+    restart::Restart,
+    var_data::VarData,
+    walker::WalkScoring,
+    watcher::Watcher,
+};
+
+pub struct Solver {
     pub model: Vec<LBool>,
     pub conflict: Vec<Lit>,
     pub verbosity: i32,
 
-    #[cfg(any(feature = "bh_chb", feature = "bh_lrb"))]
-    pub step_size: f64,
-    #[cfg(any(feature = "bh_chb", feature = "bh_lrb"))]
-    pub step_size_dec: f64,
-    #[cfg(any(feature = "bh_chb", feature = "bh_lrb"))]
-    pub min_step_size: f64,
-
-    #[cfg(feature = "bh_vsids")]
-    pub var_decay: f64,
+    pub heuristic: Box<dyn BranchingHeuristic>, // Maintains the variable activities.
+    pub other_heuristic: Box<dyn BranchingHeuristic>, // The heuristic of the inactive mode.
 
-    #[cfg(not(feature = "lbd_based_clause_deletion"))]
     pub clause_decay: f64,
+    pub core_lbd_cut: u32, // Learnt clauses with an LBD up to this are kept forever.
+    pub tier2_lbd_cut: u32, // Learnt clauses with an LBD up to this are kept while they are used.
 
     pub random_var_freq: f64,
     pub random_seed: f64,
-    pub luby_restart: bool,
+    pub restart: Restart,
     pub ccmin_mode: i32, // Controls conflict clause minimization (0=none, 1=basic, 2=deep).
     pub phase_saving: i32, // Controls the level of phase saving (0=none, 1=limited, 2=full).
-    pub rnd_pol: bool,   // Use random polarities for branching heuristics.
+    pub target_phase: i32, // Controls the use of target phases (0=none, 1=in stable mode, 2=always).
+    pub rephasing: bool,   // Periodically reset the saved phases.
+    pub walk_scoring: WalkScoring, // The scoring of the local search run when rephasing.
+    pub walk_flips: u64, // The number of flips of one local search.                                                  (default 100000)
+    pub rephase_inc: u64, // Conflicts between two rephasings grow by this much after each one.                         (default 1000)
+    pub rnd_pol: bool,    // Use random polarities for branching heuristics.
     pub rnd_init_act: bool, // Initialize variable activities with a small random value.
     pub garbage_frac: f64, // The fraction of wasted memory allowed before a garbage collection is triggered.
 
@@ -32,9 +46,31 @@ pub struct Solver<'a> {
     pub learntsize_factor: f64, // The initial limit for learnt clauses is a factor of the original clauses.                (default 1 / 3)
     pub learntsize_inc: f64, // The limit for learnt clauses is multiplied with this factor each restart.                 (default 1.1)
 
+    pub restart_margin: f64, // Glucose restarts when the fast LBD average exceeds the slow one by this factor.    (default 1.25)
+    pub restart_block: f64, // Glucose blocks a restart when the trail exceeds its average by this factor.          (default 1.4)
+    pub restart_block_start: u64, // Conflicts before Glucose restarts may be blocked.                                     (default 10000)
+    pub restart_min_conflicts: i32, // Conflicts between two Glucose restarts, or after a blocked one.                      (default 50)
+
+    pub mode_switching: bool, // Alternate between focused and stable search.
+    pub mode_switch_first: u64, // The number of conflicts of the first focused mode.                                       (default 1000)
+    pub mode_switch_inc: f64, // The factor with which the mode length is multiplied after each stable mode.             (default 2)
+    pub stable_restart_first: i32, // The initial restart limit in stable mode.                                                (default 1000)
+
+    pub lcm: bool, // Minimize the core and tier2 learnt clauses by vivification.
+    pub lcm_interval: u64, // Conflicts before the first learnt clause minimization.                                    (default 1000)
+    pub lcm_inc: u64, // The conflicts between two minimizations grow by this much after each one.               (default 1000)
+
+    pub probing: bool, // Probe failed literals before the search and periodically at restarts.
+    pub probe_interval: u64, // The conflicts between two probing rounds grow by this much after each one.              (default 10000)
+    pub probe_budget: i64, // Propagations allowed in one probing round, -1 means no budget.                            (default 100000)
+    pub probe_hbr_limit: u64, // Hyper-binary resolvents added in one probing round at most.                            (default 1000)
+
     pub learntsize_adjust_start_confl: i32,
     pub learntsize_adjust_inc: f64,
 
+    pub chrono: i32, // Backtrack chronologically when the backjump is longer than this many levels (-1=never).
+    pub conf_to_chrono: i64, // Conflicts before chronological backtracking is considered (-1=from the start).
+
     // Statistics: (read-only member variable)
     pub solves: u64,
     pub starts: u64,
@@ -47,61 +83,86 @@ pub struct Solver<'a> {
     pub learnts_literals: u64,
     pub max_literals: u64,
     pub tot_literals: u64,
+    pub bin_minimized_lits: u64, // Literals removed from learnt clauses by binary clauses.
+    pub blocked_restarts: u64,
+    pub mode_switches: u64,
+    pub rephases: u64,
+    pub walks: u64,
+    pub walk_flips_total: u64,
+    pub walk_best_unsat: usize, // The fewest falsified clauses reached by the last local search.
+    pub lcm_runs: u64,
+    pub lcm_tested_lits: u64,
+    pub lcm_removed_lits: u64,
+    pub probe_runs: u64,
+    pub probed_lits: u64,
+    pub failed_literals: u64,
+    pub probe_units: u64, // Literals found implied by both polarities of a variable.
+    pub hbr_resolvents: u64,
+    pub focused_stats: ModeStats,
+    pub stable_stats: ModeStats,
+    pub chrono_backtrack: u64,
+    pub non_chrono_backtrack: u64,
 
     pub lbd_calls: u64,
     pub lbd_seen: Vec<u64>,
-    pub picked: Vec<u64>,
-    pub conflicted: Vec<u64>,
-
-    #[cfg(feature = "almost_conflict")]
-    pub almost_conflicted: Vec<u64>,
-    #[cfg(feature = "anti_exploration")]
-    pub canceled: Vec<u64>,
-
-    #[cfg(feature = "bh_chb")]
-    pub last_conflict: Vec<u64>,
-    #[cfg(feature = "bh_chb")]
-    pub action: i32,
-    #[cfg(feature = "bh_chb")]
-    pub reward_multiplier: f64,
 
     pub total_actual_rewards: Vec<f64>,
     pub total_actual_count: Vec<i32>,
 
-    // TODO: Implement the stuff below
     // Solver state:
     pub ok: bool, // If FALSE, the constraints are already unsatisfiable. No part of the solver state may be used!
-    // pub clauses: Vec<CRef>, // List of problem clauses.
-    // pub learnts: Vec<CRef>, // List of learnt clauses.
-    // #[cfg(not(feature = "lbd_based_clause_deletion"))]
-    pub cla_inc: f64,       // Amount to bump next clause with.
+    pub clauses: Vec<CRef>, // List of problem clauses.
+    pub learnts_core: Vec<CRef>, // List of learnt clauses that are kept forever.
+    pub learnts_tier2: Vec<CRef>, // List of learnt clauses that are kept while they are used.
+    pub learnts_local: Vec<CRef>, // List of learnt clauses that are reduced by activity.
+    pub cla_inc: f64, // Amount to bump next clause with.
     pub activity: Vec<f64>, // A heuristic measurement of the activity of a variable.
-    pub var_inc: f64,       // Amount to bump next variable with.
-    // pub watches: OccLists<Lit, Vec<Watcher>, WatcherDeleted>, // 'watches[lit]' is a list of constraints watching 'lit' (will go there if literal becomes true).
-    pub assigns: Vec<LBool>,       // The current assignments.
-    pub polarity: Vec<bool>,       // The preferred polarity of each variable.
+    pub watches: OccLists<Lit, Watcher>, // 'watches[lit]' is a list of constraints watching 'lit' (will go there if literal becomes true).
+    pub watches_bin: OccLists<Lit, Watcher>, // 'watches_bin[lit]' is a list of binary clauses watching 'lit', with the other literal as blocker.
+    pub assigns: Vec<LBool>,                 // The current assignments.
+    pub polarity: Vec<bool>,                 // The preferred polarity of each variable.
+    pub original_polarity: Vec<bool>,        // The polarity of each variable when it was created.
+    pub target_polarity: Vec<bool>, // The polarities of the longest conflict-free trail since the last restart.
+    pub best_polarity: Vec<bool>, // The polarities of the longest conflict-free trail since the last rephasing.
+    pub target_assigned: usize,   // The length of the trail of 'target_polarity'.
+    pub best_assigned: usize,     // The length of the trail of 'best_polarity'.
+    pub next_rephase: u64,        // Conflict count at which to rephase next.
+    pub next_lcm: u64, // Conflict count at which to minimize the learnt clauses next, 0 before the first solve.
+    pub next_probe: u64, // Conflict count at which to probe next, 0 before the first solve.
+    pub probe_next_var: usize, // The variable the next probing round starts at.
+    pub probe_hbr_end: u64, // The probing round adds hyper-binary resolvents until 'hbr_resolvents' reaches this.
     pub decision: Vec<bool>, // Declares if a variable is eligible for selection in the decision heuristic.
     pub trail: Vec<Lit>, // Assignment stack; stores all assignments made in the order they were made.
     pub trail_lim: Vec<i32>, // Separator indices for different decision levels in 'trail'.
-    pub vardata: Vec<VarData<'a>>, // Stores reason and level for each variable.
+    pub vardata: Vec<VarData>, // Stores reason and level for each variable.
     pub qhead: i32, // Head of queue (as index into the trail -- no more explicit propagation queue in MiniSat).
-    pub simpDB_assigns: i32, // Number of top-level assignments since last execution of 'simplify()'.
-    pub simpDB_props: i64, // Remaining number of propagations that must be made before next execution of 'simplify()'.
+    pub simp_db_assigns: i32, // Number of top-level assignments since last execution of 'simplify()'.
+    pub simp_db_props: i64, // Remaining number of propagations that must be made before next execution of 'simplify()'.
     pub assumptions: Vec<Lit>, // Current set of assumptions provided to solve by the user.
-    // pub order_heap: Heap<VarOrderLt>, // A priority queue of variables ordered with respect to the variable activity.
+    pub order_heap: Heap, // A priority queue of variables ordered with respect to the variable activity.
     pub progress_estimate: f64, // Set by 'search()'.
+    pub lbd_ema_fast: Ema, // Average LBD of the recent learnt clauses.
+    pub lbd_ema_slow: Ema, // Average LBD of all learnt clauses.
+    pub trail_ema: Ema,   // Average trail size at conflicts.
+    pub mode: Mode,       // The current search mode.
+    pub mode_length: f64, // The number of conflicts of the current mode.
+    pub next_mode_switch: u64, // Conflict count at which the mode switches, 0 before the first solve.
+    pub mode_start: ModeStats, // Totals of the statistics when the current mode started.
+    pub other_activity: Vec<f64>, // The variable activities of the inactive mode.
+    pub reconstruction: ReconstructionStack, // The clauses removed by preprocessing, replayed to extend the model to the original formula.
     pub remove_satisfied: bool, // Indicates whether possibly inefficient linear scan for satisfied clauses should be performed in 'simplify'.
 
-    // ClauseAllocator     ca;
+    pub ca: ClauseAllocator,
 
     // Temporaries (to reduce allocation overhead). Each variable is prefixed by the method in which it is
     // used, exept 'seen' wich is used in several places.
-    pub seen: Vec<char>,
+    pub seen: Vec<u8>,
     pub analyze_stack: Vec<Lit>,
     pub analyze_toclear: Vec<Lit>,
     pub add_tmp: Vec<Lit>,
 
     pub max_learnts: f64,
+    pub next_tier2_reduce: u64,
     pub learntsize_adjust_confl: f64,
     pub learntsize_adjust_cnt: i32,
 
@@ -1,6 +1,6 @@
 use super::lbool::LBool;
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Var {
     pub id: i32,
 }
@@ -10,18 +10,18 @@ impl Var {
         Var { id }
     }
 
-    pub fn into_usize(&self) -> usize {
+    pub fn into_usize(self) -> usize {
         self.id as usize
     }
 
-    pub fn value(&self, values: &Vec<LBool>) -> LBool {
-        values[self.id as usize].clone()
+    pub fn value(&self, values: &[LBool]) -> LBool {
+        values[self.id as usize]
     }
 }
 
 impl PartialOrd<i32> for Var {
     fn partial_cmp(&self, other: &i32) -> Option<std::cmp::Ordering> {
-        Some(self.id.cmp(&other))
+        Some(self.id.cmp(other))
     }
 }
 
@@ -41,8 +41,8 @@ impl From<usize> for Var {
     }
 }
 
-impl Into<usize> for Var {
-    fn into(self) -> usize {
-        self.id as usize
+impl From<Var> for usize {
+    fn from(var: Var) -> Self {
+        var.id as usize
     }
 }
@@ -1,6 +1,13 @@
-use super::clause::Clause;
+use super::clause::CRef;
 
-pub struct VarData<'a> {
-    pub reason: &'a Clause,
+#[derive(Clone, Copy, Debug)]
+pub struct VarData {
+    pub reason: CRef,
     pub level: i32,
 }
+
+impl VarData {
+    pub fn new(reason: CRef, level: i32) -> Self {
+        VarData { reason, level }
+    }
+}
@@ -0,0 +1,14 @@
+/// State of the VSIDS branching heuristic.
+pub struct Vsids {
+    pub var_decay: f64,
+    pub var_inc: f64, // Amount to bump next variable with.
+}
+
+impl Default for Vsids {
+    fn default() -> Self {
+        Self {
+            var_decay: 0.95,
+            var_inc: 1.0,
+        }
+    }
+}
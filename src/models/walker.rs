@@ -0,0 +1,137 @@
+use super::lit::Lit;
+
+/// The scoring used to pick the literal to flip in a falsified clause.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum WalkScoring {
+    /// Pick a literal with a probability polynomial in its break count
+    /// (Balint & Schöning, 2012).
+    #[default]
+    ProbSat,
+    /// Pick a literal without breaks if there is one. Otherwise pick a random
+    /// literal with some probability, and one with the fewest breaks else
+    /// (Selman et al., 1994).
+    WalkSat,
+}
+
+/// The state of a local search over a set of clauses: a full assignment, the
+/// number of true literals of every clause and the falsified clauses.
+pub struct Walker {
+    pub clauses: Vec<Vec<Lit>>,
+    pub occurs: Vec<Vec<usize>>, // 'occurs[lit]' is the list of clauses containing 'lit'.
+    pub phase: Vec<bool>,        // The sign of the true literal of each variable.
+    pub num_true: Vec<u32>,      // The number of true literals of each clause.
+    pub unsat: Vec<usize>,       // The falsified clauses.
+    unsat_pos: Vec<usize>,       // Each clause's position in 'unsat', if falsified.
+}
+
+impl Walker {
+    pub fn new(phase: Vec<bool>) -> Self {
+        Walker {
+            clauses: vec![],
+            occurs: vec![vec![]; 2 * phase.len()],
+            phase,
+            num_true: vec![],
+            unsat: vec![],
+            unsat_pos: vec![],
+        }
+    }
+
+    pub fn is_true(&self, p: Lit) -> bool {
+        self.phase[p.var().into_usize()] == p.sign()
+    }
+
+    pub fn add_clause(&mut self, lits: Vec<Lit>) {
+        let c = self.clauses.len();
+        let num_true = lits.iter().filter(|&&p| self.is_true(p)).count() as u32;
+        for &p in lits.iter() {
+            self.occurs[p.into_usize()].push(c);
+        }
+        self.clauses.push(lits);
+        self.num_true.push(num_true);
+        self.unsat_pos.push(usize::MAX);
+        if num_true == 0 {
+            self.make_unsat(c);
+        }
+    }
+
+    /// The number of clauses that become falsified by flipping `v`.
+    pub fn break_count(&self, v: usize) -> u32 {
+        let t = Lit::new(v.into(), self.phase[v]);
+        self.occurs[t.into_usize()]
+            .iter()
+            .filter(|&&c| self.num_true[c] == 1)
+            .count() as u32
+    }
+
+    pub fn flip(&mut self, v: usize) {
+        let t = Lit::new(v.into(), self.phase[v]);
+        self.phase[v] = !self.phase[v];
+        for i in 0..self.occurs[t.into_usize()].len() {
+            let c = self.occurs[t.into_usize()][i];
+            self.num_true[c] -= 1;
+            if self.num_true[c] == 0 {
+                self.make_unsat(c);
+            }
+        }
+        for i in 0..self.occurs[(!t).into_usize()].len() {
+            let c = self.occurs[(!t).into_usize()][i];
+            self.num_true[c] += 1;
+            if self.num_true[c] == 1 {
+                self.make_sat(c);
+            }
+        }
+    }
+
+    fn make_unsat(&mut self, c: usize) {
+        self.unsat_pos[c] = self.unsat.len();
+        self.unsat.push(c);
+    }
+
+    fn make_sat(&mut self, c: usize) {
+        let i = self.unsat_pos[c];
+        let last = *self.unsat.last().unwrap();
+        self.unsat.swap_remove(i);
+        if last != c {
+            self.unsat_pos[last] = i;
+        }
+        self.unsat_pos[c] = usize::MAX;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::lit;
+
+    use super::*;
+
+    fn walker_with_clauses(phase: Vec<bool>, clauses: &[&[i32]]) -> Walker {
+        let mut walker = Walker::new(phase);
+        for c in clauses {
+            walker.add_clause(c.iter().map(|&x| lit(x)).collect());
+        }
+        walker
+    }
+
+    #[test]
+    fn test_unsat_clauses_follow_flips() {
+        // All variables false.
+        let mut walker = walker_with_clauses(vec![true; 3], &[&[1, 2], &[-1, 3], &[-2, -3]]);
+        assert_eq!(walker.unsat, vec![0]);
+        assert_eq!(walker.num_true, vec![0, 1, 2]);
+
+        walker.flip(0);
+        assert_eq!(walker.unsat, vec![1]);
+        walker.flip(2);
+        assert!(walker.unsat.is_empty());
+        assert_eq!(walker.num_true, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_break_count() {
+        let walker = walker_with_clauses(vec![false, true, true], &[&[1, 2], &[1, 3], &[1, -2]]);
+        // x1 is the only true literal of the first two clauses.
+        assert_eq!(walker.break_count(0), 2);
+        assert_eq!(walker.break_count(1), 0);
+        assert_eq!(walker.break_count(2), 0);
+    }
+}
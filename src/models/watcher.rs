@@ -0,0 +1,16 @@
+use super::{clause::CRef, lit::Lit};
+
+/// An entry in the watch list of a literal. The blocker is some other literal
+/// of the clause; if it is true, the clause is satisfied and does not need to
+/// be inspected.
+#[derive(Clone, Copy, Debug)]
+pub struct Watcher {
+    pub cref: CRef,
+    pub blocker: Lit,
+}
+
+impl Watcher {
+    pub fn new(cref: CRef, blocker: Lit) -> Self {
+        Watcher { cref, blocker }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::models::{lbool::LBool, lit::Lit, solver::Solver, var::Var};
+
+/// Generates a random float 0 <= x < 1. Seed must never be 0.
+pub fn drand(seed: &mut f64) -> f64 {
+    *seed *= 1389796.0;
+    let q = (*seed / 2147483647.0) as i32;
+    *seed -= q as f64 * 2147483647.0;
+    *seed / 2147483647.0
+}
+
+/// Generates a random integer 0 <= x < size. Seed must never be 0.
+pub fn irand(seed: &mut f64, size: usize) -> usize {
+    (drand(seed) * size as f64) as usize
+}
+
+impl Solver {
+    pub fn insert_var_order(&mut self, x: usize) {
+        if !self.order_heap.in_heap(x) && self.decision[x] {
+            self.order_heap.insert(x, &self.activity);
+        }
+    }
+
+    /// Puts every unassigned decision variable back into the order heap.
+    pub fn rebuild_order_heap(&mut self) {
+        let vs: Vec<usize> = (0..self.num_vars())
+            .filter(|&v| self.decision[v] && self.assigns[v] == LBool::Undefined)
+            .collect();
+        self.order_heap.build(&vs, &self.activity);
+    }
+
+    /// Picks the unassigned decision variable with the highest activity, or
+    /// with probability `random_var_freq` a random one, and returns it with
+    /// its saved or target polarity. Returns `Lit::UNDEF` if every decision
+    /// variable is assigned.
+    pub fn pick_branch_lit(&mut self) -> Lit {
+        let mut next: Option<usize> = None;
+
+        // Random decision:
+        if drand(&mut self.random_seed) < self.random_var_freq && !self.order_heap.is_empty() {
+            let v = self.order_heap[irand(&mut self.random_seed, self.order_heap.len())];
+            if self.assigns[v] == LBool::Undefined && self.decision[v] {
+                self.rnd_decisions += 1;
+            }
+            next = Some(v);
+        }
+
+        // Activity based decision:
+        while next.is_none_or(|v| self.assigns[v] != LBool::Undefined || !self.decision[v]) {
+            if self.order_heap.is_empty() {
+                next = None;
+                break;
+            }
+            self.heuristic
+                .before_pick(self.conflicts, &mut self.activity, &mut self.order_heap);
+            next = Some(self.order_heap.remove_min(&self.activity));
+        }
+
+        match next {
+            Some(v) => {
+                let sign = if self.rnd_pol {
+                    drand(&mut self.random_seed) < 0.5
+                } else if self.use_target_phase() {
+                    self.target_polarity[v]
+                } else {
+                    self.polarity[v]
+                };
+                Lit::new(Var::from(v), sign)
+            }
+            None => Lit::UNDEF,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{models::clause::CRef, test_utils::solver_with_vars};
+
+    use super::*;
+
+    #[test]
+    fn test_drand_is_deterministic_and_in_range() {
+        let mut seed1 = 91648253.0;
+        let mut seed2 = 91648253.0;
+        for _ in 0..1000 {
+            let x = drand(&mut seed1);
+            assert_eq!(x, drand(&mut seed2));
+            assert!((0.0..1.0).contains(&x));
+            assert!(irand(&mut seed1, 7) < 7);
+            irand(&mut seed2, 7);
+        }
+    }
+
+    #[test]
+    fn test_pick_highest_activity_with_saved_polarity() {
+        let mut solver = solver_with_vars(4);
+        solver.random_var_freq = 0.0;
+        solver.activity = vec![0.1, 0.4, 0.3, 0.2];
+        solver.rebuild_order_heap();
+        solver.polarity[1] = false;
+
+        assert_eq!(solver.pick_branch_lit(), Lit::new(Var::from(1), false));
+        assert_eq!(solver.pick_branch_lit(), Lit::new(Var::from(2), true));
+    }
+
+    #[test]
+    fn test_pick_skips_assigned_and_non_decision_vars() {
+        let mut solver = solver_with_vars(4);
+        solver.random_var_freq = 0.0;
+        solver.activity = vec![0.1, 0.4, 0.3, 0.2];
+        solver.rebuild_order_heap();
+        solver.set_decision_var(Var::from(2), false);
+        solver.unchecked_enqueue(Lit::new(Var::from(1), false), CRef::UNDEF);
+
+        assert_eq!(solver.pick_branch_lit().var(), Var::from(3));
+        assert_eq!(solver.pick_branch_lit().var(), Var::from(0));
+        assert_eq!(solver.pick_branch_lit(), Lit::UNDEF);
+    }
+
+    #[test]
+    fn test_cancel_until_reinserts_vars() {
+        let mut solver = solver_with_vars(2);
+        solver.random_var_freq = 0.0;
+        let p = solver.pick_branch_lit();
+        solver.new_decision_level();
+        solver.unchecked_enqueue(p, CRef::UNDEF);
+        assert!(!solver.order_heap.in_heap(p.var().into_usize()));
+
+        solver.cancel_until(0);
+        assert!(solver.order_heap.in_heap(p.var().into_usize()));
+    }
+
+    #[test]
+    fn test_random_decisions() {
+        let mut solver = solver_with_vars(10);
+        solver.random_var_freq = 1.0;
+        solver.rnd_pol = true;
+
+        let mut signs = [false; 2];
+        let mut vars = vec![];
+        loop {
+            let p = solver.pick_branch_lit();
+            if p == Lit::UNDEF {
+                break;
+            }
+            signs[p.sign() as usize] = true;
+            vars.push(p.var().into_usize());
+            solver.unchecked_enqueue(p, CRef::UNDEF);
+        }
+        vars.sort();
+        assert_eq!(vars, (0..10).collect::<Vec<_>>());
+        assert_eq!(signs, [true, true]);
+        assert!(solver.rnd_decisions > 0);
+    }
+}
@@ -0,0 +1,154 @@
+//! Conflict History-Based branching (Liang et al., AAAI 2016). Every
+//! assignment is an action whose reward favors variables that recently
+//! participated in conflicts; the activity is the Q-value of the variable.
+
+use crate::models::{branching_heuristic::BranchingHeuristic, chb::Chb, heap::Heap};
+
+impl BranchingHeuristic for Chb {
+    fn new_var(&mut self) {
+        self.last_conflict.push(0);
+    }
+
+    fn on_assign(
+        &mut self,
+        v: usize,
+        _conflicts: u64,
+        _activity: &mut [f64],
+        _order_heap: &mut Heap,
+    ) {
+        self.assigned.push(v);
+    }
+
+    fn on_conflict_var(
+        &mut self,
+        v: usize,
+        conflicts: u64,
+        _activity: &mut [f64],
+        _order_heap: &mut Heap,
+    ) {
+        self.last_conflict[v] = conflicts;
+    }
+
+    /// Updates the Q-values of the variables assigned since the last
+    /// propagation, i.e. the last decision or asserting literal and
+    /// everything it propagated.
+    fn on_propagated(
+        &mut self,
+        conflict: bool,
+        conflicts: u64,
+        activity: &mut [f64],
+        order_heap: &mut Heap,
+    ) {
+        let multiplier = if conflict {
+            1.0
+        } else {
+            self.reward_multiplier
+        };
+        for v in self.assigned.drain(..) {
+            let age = conflicts - self.last_conflict[v] + 1;
+            let reward = multiplier / age as f64;
+            let old_activity = activity[v];
+            activity[v] = self.step_size * reward + (1.0 - self.step_size) * old_activity;
+            if order_heap.in_heap(v) {
+                if activity[v] > old_activity {
+                    order_heap.decrease(v, activity);
+                } else {
+                    order_heap.increase(v, activity);
+                }
+            }
+        }
+    }
+
+    fn on_conflict(&mut self) {
+        if self.step_size > self.min_step_size {
+            self.step_size -= self.step_size_dec;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{branching_heuristic::Branching, clause::CRef, lbool::LBool, solver::Solver},
+        test_utils::{lit, solver_with_branching},
+    };
+
+    use super::*;
+
+    fn decide(solver: &mut Solver, dimacs: i32) -> CRef {
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(dimacs), CRef::UNDEF);
+        solver.propagate()
+    }
+
+    #[test]
+    fn test_reward_without_conflict_uses_multiplier() {
+        let mut chb = Chb::default();
+        let mut activity = vec![0.0; 3];
+        let mut order_heap = Heap::new();
+        for v in 0..3 {
+            chb.new_var();
+            order_heap.insert(v, &activity);
+        }
+        chb.last_conflict[0] = 1;
+        chb.on_assign(0, 3, &mut activity, &mut order_heap);
+        chb.on_assign(1, 3, &mut activity, &mut order_heap);
+        chb.on_propagated(false, 3, &mut activity, &mut order_heap);
+
+        // x1: 0.9 / (3 - 1 + 1), x2: 0.9 / (3 - 0 + 1), x3 is not assigned.
+        assert!((activity[0] - 0.4 * 0.9 / 3.0).abs() < 1e-12);
+        assert!((activity[1] - 0.4 * 0.9 / 4.0).abs() < 1e-12);
+        assert_eq!(activity[2], 0.0);
+        assert_eq!(order_heap[0], 0);
+        assert!(chb.assigned.is_empty());
+    }
+
+    #[test]
+    fn test_reward_on_conflict_is_full() {
+        let mut solver = solver_with_branching(Branching::Chb, 3, &[&[-1, 2], &[-1, -2]]);
+        solver.activity[0] = 0.5;
+
+        assert_ne!(decide(&mut solver, 1), CRef::UNDEF);
+
+        assert!((solver.activity[0] - (0.4 * 1.0 + 0.6 * 0.5)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_only_new_assignments_are_rewarded() {
+        let mut solver = solver_with_branching(Branching::Chb, 3, &[]);
+        assert_eq!(decide(&mut solver, 1), CRef::UNDEF);
+        let activity = solver.activity[0];
+
+        assert_eq!(decide(&mut solver, 2), CRef::UNDEF);
+        assert_eq!(solver.activity[0], activity);
+        assert!(solver.activity[1] > 0.0);
+    }
+
+    #[test]
+    fn test_analyze_records_last_conflict() {
+        let mut solver = solver_with_branching(Branching::Chb, 3, &[&[-1, 3], &[-2, -3, -1]]);
+        assert_eq!(decide(&mut solver, 2), CRef::UNDEF);
+        solver.conflicts = 7;
+        let confl = decide(&mut solver, 1);
+        assert_ne!(confl, CRef::UNDEF);
+
+        let mut learnt = vec![];
+        solver.analyze(confl, &mut learnt);
+
+        // x1, x2 and x3 were all in the conflict: the next reward has age 1.
+        let before = solver.activity.clone();
+        solver.cancel_until(0);
+        assert_eq!(decide(&mut solver, 2), CRef::UNDEF);
+        assert!((solver.activity[1] - (0.4 * 0.9 + 0.6 * before[1])).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_solve_with_chb() {
+        let mut solver = solver_with_branching(
+            Branching::Chb,
+            4,
+            &[&[1, 2], &[-1, 3], &[-2, 3], &[-3, 4], &[-3, -4, 1]],
+        );
+        assert_eq!(solver.solve(), LBool::True);
+    }
+}
@@ -0,0 +1,402 @@
+#[cfg(feature = "lbd_based_clause_deletion")]
+use crate::models::clause::{CORE, TIER2};
+use crate::models::{
+    clause::{CRef, LOCAL},
+    solver::Solver,
+};
+
+/// Number of conflicts between two reductions of the tier2 clauses.
+#[cfg(feature = "lbd_based_clause_deletion")]
+pub const TIER2_REDUCE_INTERVAL: u64 = 10000;
+/// Number of conflicts without use after which a tier2 clause is demoted.
+#[cfg(feature = "lbd_based_clause_deletion")]
+const TIER2_INACTIVE_LIMIT: u32 = 30000;
+
+impl Solver {
+    /// Computes the literal block distance of the clause: the number of
+    /// distinct decision levels among its literals.
+    pub fn compute_lbd(&mut self, cr: CRef) -> u32 {
+        self.lbd_calls += 1;
+        let mut lbd = 0;
+        for k in 0..self.ca[cr].size() {
+            let l = self.level(self.ca[cr][k].var()) as usize;
+            if l >= self.lbd_seen.len() {
+                self.lbd_seen.resize(l + 1, 0);
+            }
+            if self.lbd_seen[l] != self.lbd_calls {
+                lbd += 1;
+                self.lbd_seen[l] = self.lbd_calls;
+            }
+        }
+        lbd
+    }
+
+    /// Puts a new learnt clause into the tier matching its LBD. Without
+    /// LBD-based clause deletion, every learnt clause is local.
+    pub fn add_learnt(&mut self, cr: CRef) {
+        #[cfg(feature = "lbd_based_clause_deletion")]
+        {
+            let lbd = self.compute_lbd(cr);
+            self.ca[cr].set_lbd(lbd);
+            if lbd <= self.core_lbd_cut {
+                self.ca[cr].set_mark(CORE);
+                self.learnts_core.push(cr);
+                return;
+            } else if lbd <= self.tier2_lbd_cut {
+                self.ca[cr].set_mark(TIER2);
+                self.ca[cr].set_touched(self.conflicts as u32);
+                self.learnts_tier2.push(cr);
+                return;
+            }
+        }
+        self.learnts_local.push(cr);
+        self.cla_bump_activity(cr);
+    }
+
+    /// Called for every learnt clause used in conflict analysis. Promotes
+    /// the clause if its LBD improved, and records that it was used.
+    pub fn bump_learnt(&mut self, cr: CRef) {
+        #[cfg(feature = "lbd_based_clause_deletion")]
+        if self.ca[cr].mark() != CORE {
+            let lbd = self.compute_lbd(cr);
+            if lbd < self.ca[cr].lbd() {
+                self.ca[cr].set_lbd(lbd);
+                if lbd <= self.core_lbd_cut {
+                    self.ca[cr].set_mark(CORE);
+                    self.learnts_core.push(cr);
+                } else if lbd <= self.tier2_lbd_cut && self.ca[cr].mark() == LOCAL {
+                    self.ca[cr].set_mark(TIER2);
+                    self.learnts_tier2.push(cr);
+                }
+            }
+        }
+
+        match self.ca[cr].mark() {
+            #[cfg(feature = "lbd_based_clause_deletion")]
+            TIER2 => self.ca[cr].set_touched(self.conflicts as u32),
+            LOCAL => self.cla_bump_activity(cr),
+            _ => {}
+        }
+    }
+
+    pub fn cla_bump_activity(&mut self, cr: CRef) {
+        let activity = self.ca[cr].activity() + self.cla_inc as f32;
+        self.ca[cr].set_activity(activity);
+        if activity > 1e20 {
+            // Rescale:
+            for &l in self.learnts_local.iter() {
+                let a = self.ca[l].activity();
+                self.ca[l].set_activity(a * 1e-20);
+            }
+            self.cla_inc *= 1e-20;
+        }
+    }
+
+    pub fn cla_decay_activity(&mut self) {
+        self.cla_inc *= 1.0 / self.clause_decay;
+    }
+
+    /// Number of learnt clauses over all tiers.
+    pub fn num_learnts(&self) -> usize {
+        self.learnts_core.len() + self.learnts_tier2.len() + self.learnts_local.len()
+    }
+
+    /// Demotes the tier2 clauses that have not been used in conflict analysis
+    /// for `TIER2_INACTIVE_LIMIT` conflicts to the local tier.
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    pub fn reduce_db_tier2(&mut self) {
+        // A clause promoted from the local tier left an entry behind there,
+        // which must be gone before the clause is demoted and added again:
+        let ca = &self.ca;
+        self.learnts_local.retain(|&cr| ca[cr].mark() == LOCAL);
+
+        let mut learnts = std::mem::take(&mut self.learnts_tier2);
+        learnts.retain(|&cr| {
+            if self.ca[cr].mark() != TIER2 {
+                // (promoted to core)
+                return false;
+            }
+            let inactive = (self.conflicts as u32).wrapping_sub(self.ca[cr].touched());
+            if !self.locked(cr) && inactive > TIER2_INACTIVE_LIMIT {
+                self.ca[cr].set_mark(LOCAL);
+                self.ca[cr].set_activity(0.0);
+                self.learnts_local.push(cr);
+                self.cla_bump_activity(cr);
+                false
+            } else {
+                true
+            }
+        });
+        self.learnts_tier2 = learnts;
+    }
+
+    /// Removes half of the local learnt clauses, those with the lowest
+    /// activity first, and any clause with an activity below
+    /// `cla_inc / learnts`. Binary clauses and reasons are kept.
+    pub fn reduce_db(&mut self) {
+        let mut learnts = std::mem::take(&mut self.learnts_local);
+        // (drop the entries of promoted clauses)
+        learnts.retain(|&cr| self.ca[cr].mark() == LOCAL);
+        let extra_lim = self.cla_inc / learnts.len() as f64; // Remove any clause below this activity
+
+        // Binary clauses last, otherwise by increasing activity:
+        learnts.sort_by(|&x, &y| {
+            let (x, y) = (&self.ca[x], &self.ca[y]);
+            (x.size() == 2)
+                .cmp(&(y.size() == 2))
+                .then(x.activity().total_cmp(&y.activity()))
+        });
+
+        let limit = learnts.len() / 2;
+        let mut i = 0;
+        learnts.retain(|&cr| {
+            let c = &self.ca[cr];
+            let remove = c.size() > 2
+                && !self.locked(cr)
+                && (i < limit || (c.activity() as f64) < extra_lim);
+            i += 1;
+            if remove {
+                self.remove_clause(cr);
+            }
+            !remove
+        });
+        self.learnts_local = learnts;
+        self.check_garbage();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{lbool::LBool, lit::Lit},
+        test_utils::{clause, lit, solver_with_vars},
+    };
+
+    use super::*;
+
+    fn add_local(solver: &mut Solver, dimacs: &[i32], activity: f32) -> CRef {
+        let cr = solver.ca.alloc(&clause(dimacs), true);
+        solver.ca[cr].set_activity(activity);
+        solver.learnts_local.push(cr);
+        solver.attach_clause(cr);
+        cr
+    }
+
+    /// Decides the literals in order, one per level.
+    fn decide(solver: &mut Solver, dimacs: &[i32]) {
+        for &d in dimacs {
+            solver.new_decision_level();
+            solver.unchecked_enqueue(lit(d), CRef::UNDEF);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    fn test_compute_lbd_counts_levels() {
+        let mut solver = solver_with_vars(5);
+        decide(&mut solver, &[1, 2, 3]);
+        // x4 is on level 3 with x3, x5 is unassigned at level 0.
+        solver.unchecked_enqueue(lit(4), CRef::UNDEF);
+        let cr = solver.ca.alloc(&clause(&[-1, -3, -4, 5]), true);
+
+        assert_eq!(solver.compute_lbd(cr), 3);
+        assert_eq!(solver.compute_lbd(cr), 3);
+        assert_eq!(solver.lbd_calls, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    fn test_add_learnt_picks_tier_by_lbd() {
+        let mut solver = solver_with_vars(9);
+        decide(&mut solver, &[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let core = solver.ca.alloc(&clause(&[-1, -2]), true);
+        solver.add_learnt(core);
+        let tier2 = solver.ca.alloc(&clause(&[-1, -2, -3, -4, -5, -6]), true);
+        solver.add_learnt(tier2);
+        let local = solver
+            .ca
+            .alloc(&clause(&[-1, -2, -3, -4, -5, -6, -7]), true);
+        solver.add_learnt(local);
+
+        assert_eq!(solver.learnts_core, vec![core]);
+        assert_eq!(solver.learnts_tier2, vec![tier2]);
+        assert_eq!(solver.learnts_local, vec![local]);
+        assert_eq!(solver.ca[core].mark(), CORE);
+        assert_eq!(solver.ca[tier2].mark(), TIER2);
+        assert_eq!(solver.ca[local].lbd(), 7);
+        assert!(solver.ca[local].activity() > 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    fn test_bump_learnt_promotes_on_better_lbd() {
+        let mut solver = solver_with_vars(8);
+        decide(&mut solver, &[1, 2, 3, 4, 5, 6, 7]);
+        let cr = solver
+            .ca
+            .alloc(&clause(&[-1, -2, -3, -4, -5, -6, -7]), true);
+        solver.add_learnt(cr);
+        assert_eq!(solver.ca[cr].mark(), LOCAL);
+
+        // The same literals on fewer levels:
+        solver.cancel_until(0);
+        decide(&mut solver, &[1, 2, 3, 4]);
+        for d in [5, 6, 7] {
+            solver.unchecked_enqueue(lit(d), CRef::UNDEF);
+        }
+        solver.conflicts = 42;
+        solver.bump_learnt(cr);
+        assert_eq!(solver.ca[cr].mark(), TIER2);
+        assert_eq!(solver.ca[cr].lbd(), 4);
+        assert_eq!(solver.ca[cr].touched(), 42);
+        assert_eq!(solver.learnts_tier2, vec![cr]);
+
+        solver.cancel_until(0);
+        decide(&mut solver, &[1, 2]);
+        for d in [3, 4, 5, 6, 7] {
+            solver.unchecked_enqueue(lit(d), CRef::UNDEF);
+        }
+        solver.bump_learnt(cr);
+        assert_eq!(solver.ca[cr].mark(), CORE);
+        assert_eq!(solver.learnts_core, vec![cr]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "lbd_based_clause_deletion"))]
+    fn test_add_learnt_without_lbd_is_local() {
+        let mut solver = solver_with_vars(3);
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(1), CRef::UNDEF);
+        let cr = solver.ca.alloc(&clause(&[-1, 2]), true);
+        solver.add_learnt(cr);
+
+        assert!(solver.learnts_core.is_empty());
+        assert_eq!(solver.learnts_local, vec![cr]);
+        assert_eq!(solver.ca[cr].mark(), LOCAL);
+        assert_eq!(solver.ca[cr].activity(), 1.0);
+    }
+
+    #[test]
+    fn test_bump_learnt_bumps_local_activity() {
+        let mut solver = solver_with_vars(3);
+        let cr = add_local(&mut solver, &[1, 2, 3], 0.0);
+        #[cfg(feature = "lbd_based_clause_deletion")]
+        solver.ca[cr].set_lbd(1);
+        solver.bump_learnt(cr);
+        assert_eq!(solver.ca[cr].activity(), 1.0);
+
+        solver.cla_decay_activity();
+        solver.bump_learnt(cr);
+        assert!((solver.ca[cr].activity() - (1.0 + 1.0 / 0.999)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_cla_bump_rescales_large_activities() {
+        let mut solver = solver_with_vars(3);
+        let cr1 = add_local(&mut solver, &[1, 2, 3], 1e20);
+        let cr2 = add_local(&mut solver, &[-1, 2, 3], 0.0);
+        solver.cla_inc = 2e20;
+        solver.cla_bump_activity(cr2);
+
+        assert!((solver.ca[cr1].activity() - 1.0).abs() < 1e-6);
+        assert!((solver.ca[cr2].activity() - 2.0).abs() < 1e-6);
+        assert!((solver.cla_inc - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    fn test_reduce_db_tier2_demotes_inactive_clauses() {
+        let mut solver = solver_with_vars(4);
+        let old = solver.ca.alloc(&clause(&[1, 2, 3]), true);
+        let recent = solver.ca.alloc(&clause(&[2, 3, 4]), true);
+        for (cr, touched) in [(old, 1000), (recent, 30000)] {
+            solver.ca[cr].set_mark(TIER2);
+            solver.ca[cr].set_touched(touched);
+            solver.learnts_tier2.push(cr);
+            solver.attach_clause(cr);
+        }
+        solver.conflicts = 40000;
+
+        solver.reduce_db_tier2();
+        assert_eq!(solver.learnts_tier2, vec![recent]);
+        assert_eq!(solver.learnts_local, vec![old]);
+        assert_eq!(solver.ca[old].mark(), LOCAL);
+    }
+
+    #[test]
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    fn test_demoted_clause_is_removed_once() {
+        let mut solver = solver_with_vars(8);
+        for d in [[1, 2, 3], [2, 3, 4], [3, 4, 5]] {
+            add_local(&mut solver, &d, 10.0);
+        }
+        decide(&mut solver, &[1, 2, 3, 4, 5, 6, 7]);
+        let cr = solver
+            .ca
+            .alloc(&clause(&[-1, -2, -3, -4, -5, -6, -7]), true);
+        solver.attach_clause(cr);
+        solver.add_learnt(cr);
+
+        // Promoted to tier2, then demoted after a long time without use:
+        solver.cancel_until(0);
+        decide(&mut solver, &[1, 2, 3, 4]);
+        for d in [5, 6, 7] {
+            solver.unchecked_enqueue(lit(d), CRef::UNDEF);
+        }
+        solver.bump_learnt(cr);
+        assert_eq!(solver.ca[cr].mark(), TIER2);
+        solver.cancel_until(0);
+        solver.conflicts = 40000;
+        solver.reduce_db_tier2();
+        assert_eq!(solver.ca[cr].mark(), LOCAL);
+        assert_eq!(solver.learnts_local.iter().filter(|&&x| x == cr).count(), 1);
+
+        // It has the lowest activity, so it goes first, and only once:
+        solver.reduce_db();
+        assert_eq!(solver.learnts_local.len(), 2);
+        assert!(!solver.learnts_local.contains(&cr));
+        assert_eq!(solver.learnts_literals, 6);
+    }
+
+    #[test]
+    fn test_reduce_db_removes_half_with_lowest_activity() {
+        let mut solver = solver_with_vars(6);
+        add_local(&mut solver, &[1, 2, 3], 5.0);
+        add_local(&mut solver, &[1, 4], 0.5);
+        add_local(&mut solver, &[2, 3, 4], 4.0);
+        add_local(&mut solver, &[3, 4, 5], 1.0);
+        add_local(&mut solver, &[4, 5, 6], 2.0);
+        add_local(&mut solver, &[1, 5, 6], 3.0);
+
+        solver.reduce_db();
+
+        // Half of the clauses go, lowest activity first, but binary clauses
+        // are sorted last.
+        let mut kept: Vec<Vec<Lit>> = solver
+            .learnts_local
+            .iter()
+            .map(|&cr| solver.ca[cr].lits().to_vec())
+            .collect();
+        kept.sort();
+        let mut expected = vec![clause(&[1, 2, 3]), clause(&[1, 4]), clause(&[2, 3, 4])];
+        expected.sort();
+        assert_eq!(kept, expected);
+    }
+
+    #[test]
+    fn test_reduce_db_keeps_locked_clauses() {
+        let mut solver = solver_with_vars(4);
+        let reason = add_local(&mut solver, &[3, -1, -2], 1.0);
+        add_local(&mut solver, &[2, 3, 4], 5.0);
+        decide(&mut solver, &[1, 2]);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(solver.reason(lit(3).var()), reason);
+
+        // The reason has the lowest activity, but cannot be removed.
+        solver.reduce_db();
+        assert_eq!(solver.learnts_local.len(), 2);
+        assert!(solver.learnts_local.contains(&solver.reason(lit(3).var())));
+        assert_eq!(solver.lit_value(&lit(3)), LBool::True);
+    }
+}
@@ -0,0 +1,493 @@
+use crate::models::{
+    clause::CRef, lbool::LBool, lit::Lit, solver::Solver, var::Var, watcher::Watcher,
+};
+
+/// Learnt clauses up to this size are minimized with binary clauses.
+const BINARY_MINIMIZATION_MAX_SIZE: usize = 30;
+
+impl Solver {
+    /// Analyzes the conflict and produces a reason clause.
+    ///
+    /// Pre-conditions:
+    /// - `out_learnt` is assumed to be cleared.
+    /// - Current decision level must be greater than root level.
+    /// - At least two literals of the conflict clause are assigned at its
+    ///   highest level, which may be below the current decision level after
+    ///   chronological backtracking.
+    ///
+    /// Post-conditions:
+    /// - `out_learnt[0]` is the asserting literal at the returned backtrack level.
+    /// - If `out_learnt.len() > 1` then `out_learnt[1]` has the greatest decision
+    ///   level of the rest of literals. There may be others from the same level
+    ///   though.
+    pub fn analyze(&mut self, mut confl: CRef, out_learnt: &mut Vec<Lit>) -> i32 {
+        let mut path_c = 0;
+        let mut p = Lit::UNDEF;
+
+        // Generate conflict clause:
+        out_learnt.push(Lit::UNDEF); // (leave room for the asserting literal)
+        let mut index = self.trail.len();
+        let conflict_level = self.ca[confl]
+            .iter()
+            .map(|q| self.level(q.var()))
+            .max()
+            .unwrap();
+
+        loop {
+            assert!(confl != CRef::UNDEF); // (otherwise should be UIP)
+            if self.ca[confl].learnt() {
+                self.bump_learnt(confl);
+            }
+
+            let start = if p == Lit::UNDEF { 0 } else { 1 };
+            for j in start..self.ca[confl].size() {
+                let q = self.ca[confl][j];
+                let v = q.var().into_usize();
+
+                if self.seen[v] == 0 && self.level(q.var()) > 0 {
+                    self.heuristic.on_conflict_var(
+                        v,
+                        self.conflicts,
+                        &mut self.activity,
+                        &mut self.order_heap,
+                    );
+                    self.seen[v] = 1;
+                    if self.level(q.var()) >= conflict_level {
+                        path_c += 1;
+                    } else {
+                        out_learnt.push(q);
+                    }
+                }
+            }
+
+            // Select next clause to look at, skipping the out of order
+            // literals below the conflict level:
+            loop {
+                index -= 1;
+                while self.seen[self.trail[index].var().into_usize()] == 0 {
+                    index -= 1;
+                }
+                p = self.trail[index];
+                if self.level(p.var()) >= conflict_level {
+                    break;
+                }
+            }
+            confl = self.reason(p.var());
+            self.seen[p.var().into_usize()] = 0;
+            path_c -= 1;
+
+            if path_c == 0 {
+                break;
+            }
+        }
+        out_learnt[0] = !p;
+
+        // Simplify conflict clause:
+        self.analyze_toclear.clone_from(out_learnt);
+        let before = out_learnt.len();
+        if self.ccmin_mode == 2 {
+            // Maintain an abstraction of levels involved in conflict:
+            let abstract_levels = out_learnt[1..]
+                .iter()
+                .fold(0, |levels, q| levels | self.abstract_level(q.var()));
+
+            let mut j = 1;
+            for i in 1..out_learnt.len() {
+                let q = out_learnt[i];
+                if self.reason(q.var()) == CRef::UNDEF || !self.lit_redundant(q, abstract_levels) {
+                    out_learnt[j] = q;
+                    j += 1;
+                }
+            }
+            out_learnt.truncate(j);
+        } else if self.ccmin_mode == 1 {
+            let mut j = 1;
+            for i in 1..out_learnt.len() {
+                let q = out_learnt[i];
+                let reason = self.reason(q.var());
+
+                let keep = reason == CRef::UNDEF
+                    || self.ca[reason]
+                        .iter()
+                        .skip(1)
+                        .any(|r| self.seen[r.var().into_usize()] == 0 && self.level(r.var()) > 0);
+                if keep {
+                    out_learnt[j] = q;
+                    j += 1;
+                }
+            }
+            out_learnt.truncate(j);
+        }
+        if out_learnt.len() <= BINARY_MINIMIZATION_MAX_SIZE {
+            self.binary_minimize(out_learnt);
+        }
+        self.max_literals += before as u64;
+        self.tot_literals += out_learnt.len() as u64;
+
+        // Find correct backtrack level:
+        let out_btlevel = if out_learnt.len() == 1 {
+            0
+        } else {
+            // Find the first literal assigned at the next-highest level:
+            let mut max_i = 1;
+            for i in 2..out_learnt.len() {
+                if self.level(out_learnt[i].var()) > self.level(out_learnt[max_i].var()) {
+                    max_i = i;
+                }
+            }
+            // Swap-in this literal at index 1:
+            out_learnt.swap(1, max_i);
+            self.level(out_learnt[1].var())
+        };
+
+        if self.heuristic.wants_reason_vars() {
+            self.report_reason_vars(out_learnt);
+        }
+
+        // ('seen[]' is now cleared)
+        for q in self.analyze_toclear.iter() {
+            self.seen[q.var().into_usize()] = 0;
+        }
+
+        out_btlevel
+    }
+
+    /// Checks if `p` can be removed from the learnt clause: it is, if every
+    /// path through its reasons ends in a literal already in the clause.
+    /// `abstract_levels` is used to abort early if the algorithm is visiting
+    /// literals at levels that cannot be removed later.
+    fn lit_redundant(&mut self, p: Lit, abstract_levels: u32) -> bool {
+        self.analyze_stack.clear();
+        self.analyze_stack.push(p);
+        let top = self.analyze_toclear.len();
+        while let Some(q) = self.analyze_stack.pop() {
+            let reason = self.reason(q.var());
+            assert!(reason != CRef::UNDEF);
+
+            for i in 1..self.ca[reason].size() {
+                let r = self.ca[reason][i];
+                let v = r.var();
+                if self.seen[v.into_usize()] == 0 && self.level(v) > 0 {
+                    if self.reason(v) != CRef::UNDEF
+                        && (self.abstract_level(v) & abstract_levels) != 0
+                    {
+                        self.seen[v.into_usize()] = 1;
+                        self.analyze_stack.push(r);
+                        self.analyze_toclear.push(r);
+                    } else {
+                        for q in self.analyze_toclear[top..].iter() {
+                            self.seen[q.var().into_usize()] = 0;
+                        }
+                        self.analyze_toclear.truncate(top);
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Removes the literals `!q` of the learnt clause for which a binary
+    /// clause `(out_learnt[0] q)` exists: resolving with it drops `!q` and
+    /// adds nothing. Expects `seen` to be set for the learnt literals and
+    /// leaves it set.
+    fn binary_minimize(&mut self, out_learnt: &mut Vec<Lit>) {
+        // Mark the literals of the clause that may be removed:
+        for q in out_learnt[1..].iter() {
+            self.seen[q.var().into_usize()] = 2;
+        }
+
+        let mut to_remove = 0;
+        for w in self.watches_bin[!out_learnt[0]].iter() {
+            let the_other = w.blocker;
+            let v = the_other.var().into_usize();
+            if self.seen[v] == 2 && the_other.value(&self.assigns) == LBool::True {
+                self.seen[v] = 3;
+                to_remove += 1;
+            }
+        }
+
+        if to_remove > 0 {
+            let mut j = 1;
+            for i in 1..out_learnt.len() {
+                let q = out_learnt[i];
+                if self.seen[q.var().into_usize()] != 3 {
+                    out_learnt[j] = q;
+                    j += 1;
+                }
+            }
+            out_learnt.truncate(j);
+            self.bin_minimized_lits += to_remove;
+        }
+        for q in self.analyze_toclear[1..].iter() {
+            self.seen[q.var().into_usize()] = 1;
+        }
+    }
+
+    /// Returns the highest decision level in the conflict clause and whether
+    /// it is the level of a single literal, which then is a missed implication
+    /// rather than a conflict. The literal is moved to the first watch.
+    pub fn find_conflict_level(&mut self, confl: CRef) -> (i32, bool) {
+        let c = &self.ca[confl];
+        let mut highest_level = self.level(c[0].var());
+        let mut highest_ind = 0;
+        let mut only_one = true;
+        for i in 1..c.size() {
+            let level = self.level(c[i].var());
+            if level > highest_level {
+                highest_level = level;
+                highest_ind = i;
+                only_one = true;
+            } else if level == highest_level {
+                only_one = false;
+            }
+        }
+
+        if highest_ind != 0 {
+            let c = &mut self.ca[confl];
+            c.swap(0, highest_ind);
+            if highest_ind > 1 {
+                // The literal now at 'highest_ind' is no longer watched:
+                let (c0, c1, old) = (c[0], c[1], c[highest_ind]);
+                let ws = &mut self.watches[!old];
+                let k = ws.iter().position(|w| w.cref == confl).unwrap();
+                ws.remove(k);
+                self.watches[!c0].push(Watcher::new(confl, c1));
+            }
+        }
+
+        (highest_level, only_one)
+    }
+
+    /// Specialized analysis procedure to express the final conflict in terms
+    /// of assumptions. Calculates the (possibly empty) set of assumptions that
+    /// led to the assignment of `p`, and stores the result in `out_conflict`.
+    pub fn analyze_final(&mut self, p: Lit, out_conflict: &mut Vec<Lit>) {
+        out_conflict.clear();
+        out_conflict.push(p);
+
+        if self.decision_level() == 0 || self.level(p.var()) == 0 {
+            return;
+        }
+
+        self.seen[p.var().into_usize()] = 1;
+
+        for i in (self.trail_lim[0] as usize..self.trail.len()).rev() {
+            let x = self.trail[i].var();
+            if self.seen[x.into_usize()] != 0 {
+                let reason = self.reason(x);
+                if reason == CRef::UNDEF {
+                    assert!(self.level(x) > 0);
+                    out_conflict.push(!self.trail[i]);
+                } else {
+                    for j in 1..self.ca[reason].size() {
+                        let q = self.ca[reason][j];
+                        if self.level(q.var()) > 0 {
+                            self.seen[q.var().into_usize()] = 1;
+                        }
+                    }
+                }
+                self.seen[x.into_usize()] = 0;
+            }
+        }
+
+        self.seen[p.var().into_usize()] = 0;
+    }
+
+    /// Reports the variables in the reasons of the learnt clause, which took
+    /// part in the conflict without appearing in the clause itself, to the
+    /// branching heuristic.
+    ///
+    /// Pre-condition: `seen` is set for the literals of `out_learnt`.
+    fn report_reason_vars(&mut self, out_learnt: &[Lit]) {
+        self.seen[out_learnt[0].var().into_usize()] = 1;
+        for &q in out_learnt.iter().rev() {
+            let reason = self.reason(q.var());
+            if reason == CRef::UNDEF {
+                continue;
+            }
+            for k in 0..self.ca[reason].size() {
+                let l = self.ca[reason][k];
+                let v = l.var().into_usize();
+                if self.seen[v] == 0 {
+                    self.seen[v] = 1;
+                    self.heuristic.on_reason_var(v);
+                    self.analyze_toclear.push(l);
+                }
+            }
+        }
+    }
+
+    /// Used to represent an abstraction of sets of decision levels.
+    fn abstract_level(&self, x: Var) -> u32 {
+        1 << (self.level(x) & 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{clause, lit, solver_with_clauses, sorted};
+
+    /// Makes the decisions in order, one per level, and returns the conflict
+    /// reached after the last one.
+    fn decide_until_conflict(solver: &mut Solver, decisions: &[i32]) -> CRef {
+        for (i, &d) in decisions.iter().enumerate() {
+            solver.new_decision_level();
+            solver.unchecked_enqueue(lit(d), CRef::UNDEF);
+            let confl = solver.propagate();
+            if i + 1 < decisions.len() {
+                assert_eq!(confl, CRef::UNDEF);
+            } else {
+                assert_ne!(confl, CRef::UNDEF);
+                return confl;
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_find_conflict_level() {
+        let mut solver = solver_with_clauses(4, &[]);
+        for d in [1, 2, 3] {
+            solver.new_decision_level();
+            solver.unchecked_enqueue(lit(d), CRef::UNDEF);
+        }
+        solver.unchecked_enqueue_at(lit(4), 2, CRef::UNDEF);
+
+        // Two literals at the second level: a real conflict.
+        let cr = solver.ca.alloc(&clause(&[-1, -2, -4]), false);
+        solver.attach_clause(cr);
+        assert_eq!(solver.find_conflict_level(cr), (2, false));
+
+        // A single literal at the third level, which becomes watched.
+        let cr = solver.ca.alloc(&clause(&[-1, -2, -3]), false);
+        solver.attach_clause(cr);
+        assert_eq!(solver.find_conflict_level(cr), (3, true));
+        assert_eq!(solver.ca[cr][0], lit(-3));
+        assert!(solver.watches[lit(3)].iter().any(|w| w.cref == cr));
+        assert!(!solver.watches[lit(1)].iter().any(|w| w.cref == cr));
+    }
+
+    #[test]
+    fn test_analyze_finds_first_uip() {
+        // Level 1: x1. Level 2: x2. Level 3: x3 -> x4, x5 -> x6 -> x7 -> conflict.
+        let mut solver = solver_with_clauses(
+            7,
+            &[
+                &[-3, 4],
+                &[-3, -1, 5],
+                &[-4, -5, 6],
+                &[-6, -2, 7],
+                &[-6, -7],
+            ],
+        );
+        let confl = decide_until_conflict(&mut solver, &[1, 2, 3]);
+
+        let mut learnt = vec![];
+        let btlevel = solver.analyze(confl, &mut learnt);
+
+        // x6 dominates every path from x3 to the conflict.
+        assert_eq!(learnt, clause(&[-6, -2]));
+        assert_eq!(btlevel, 2);
+        assert!(solver.seen.iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn test_analyze_unit_learnt_backtracks_to_root() {
+        let mut solver = solver_with_clauses(3, &[&[-1, 2], &[-1, 3], &[-2, -3]]);
+        let confl = decide_until_conflict(&mut solver, &[1]);
+
+        let mut learnt = vec![];
+        let btlevel = solver.analyze(confl, &mut learnt);
+
+        assert_eq!(learnt, clause(&[-1]));
+        assert_eq!(btlevel, 0);
+    }
+
+    #[test]
+    fn test_analyze_puts_highest_level_second() {
+        // Level 1: x1. Level 2: x2. Level 3: x3. Level 4: x4 -> conflict.
+        let mut solver = solver_with_clauses(5, &[&[-4, -1, 5], &[-4, -3, -2, -5]]);
+        let confl = decide_until_conflict(&mut solver, &[1, 2, 3, 4]);
+
+        let mut learnt = vec![];
+        let btlevel = solver.analyze(confl, &mut learnt);
+
+        assert_eq!(learnt[0], lit(-4));
+        assert_eq!(learnt[1], lit(-3));
+        assert_eq!(sorted(&learnt), sorted(&clause(&[-4, -3, -2, -1])));
+        assert_eq!(btlevel, 3);
+    }
+
+    // Level 1: x1 -> x2 -> x4. Level 2: x3 -> x5 -> conflict on (-3 -5 -1 -4).
+    // x4 is implied by x1 through x2, which is not in the learnt clause.
+    fn minimization_solver(ccmin_mode: i32) -> (Solver, CRef) {
+        let mut solver = solver_with_clauses(5, &[&[-1, 2], &[-2, 4], &[-3, 5], &[-3, -5, -1, -4]]);
+        solver.ccmin_mode = ccmin_mode;
+        let confl = decide_until_conflict(&mut solver, &[1, 3]);
+        (solver, confl)
+    }
+
+    #[test]
+    fn test_analyze_without_minimization() {
+        let (mut solver, confl) = minimization_solver(0);
+        let mut learnt = vec![];
+        let btlevel = solver.analyze(confl, &mut learnt);
+
+        assert_eq!(learnt[0], lit(-3));
+        assert_eq!(sorted(&learnt), sorted(&clause(&[-3, -1, -4])));
+        assert_eq!(btlevel, 1);
+    }
+
+    #[test]
+    fn test_analyze_basic_minimization_keeps_indirect_implication() {
+        let (mut solver, confl) = minimization_solver(1);
+        let mut learnt = vec![];
+        solver.analyze(confl, &mut learnt);
+
+        // The reason of x4 contains x2, which is not in the clause.
+        assert_eq!(sorted(&learnt), sorted(&clause(&[-3, -1, -4])));
+    }
+
+    #[test]
+    fn test_analyze_deep_minimization_removes_indirect_implication() {
+        let (mut solver, confl) = minimization_solver(2);
+        let mut learnt = vec![];
+        let btlevel = solver.analyze(confl, &mut learnt);
+
+        assert_eq!(learnt, clause(&[-3, -1]));
+        assert_eq!(btlevel, 1);
+        assert!(solver.seen.iter().all(|&s| s == 0));
+        assert_eq!(solver.max_literals, 3);
+        assert_eq!(solver.tot_literals, 2);
+    }
+
+    #[test]
+    fn test_analyze_basic_minimization_removes_direct_implication() {
+        // Level 1: x1 -> x2. Level 2: x3 -> conflict on (-3 -1 -2).
+        let mut solver = solver_with_clauses(4, &[&[-1, 2], &[-3, 4], &[-3, -4, -1, -2]]);
+        solver.ccmin_mode = 1;
+        let confl = decide_until_conflict(&mut solver, &[1, 3]);
+        let mut learnt = vec![];
+        solver.analyze(confl, &mut learnt);
+
+        assert_eq!(learnt, clause(&[-3, -1]));
+    }
+
+    #[test]
+    fn test_analyze_binary_minimization() {
+        // Level 1: x1. Level 2: x2. Level 3: x3 -> x4 -> conflict on (-3 -4 -1 -2).
+        // Resolving with (-3 2) removes -2, which deep minimization keeps.
+        let mut solver = solver_with_clauses(4, &[&[-3, 2], &[-3, 4], &[-3, -4, -1, -2]]);
+        let confl = decide_until_conflict(&mut solver, &[1, 2, 3]);
+        let mut learnt = vec![];
+        let btlevel = solver.analyze(confl, &mut learnt);
+
+        assert_eq!(learnt, clause(&[-3, -1]));
+        assert_eq!(btlevel, 1);
+        assert_eq!(solver.bin_minimized_lits, 1);
+        assert!(solver.seen.iter().all(|&s| s == 0));
+    }
+}
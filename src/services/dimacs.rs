@@ -1,55 +1,164 @@
+use crate::models::lbool::LBool;
 use crate::models::lit::Lit;
+use crate::models::simp_solver::SimpSolver;
 use crate::models::solver::Solver;
-use std::io::BufRead;
+use crate::models::var::Var;
+use std::io::{self, BufRead, Write};
 
-impl<'a> Solver<'a> {
+/// Parses a DIMACS formula and passes each clause to `add_clause`, together
+/// with the number of variables it needs. Returns the number of variables
+/// declared in the header.
+fn parse_dimacs<T: BufRead>(reader: T, mut add_clause: impl FnMut(Vec<Lit>, usize)) -> usize {
+    let mut num_vars: usize = 0;
+    let mut num_clauses: usize = 0;
+    let mut num_found_clauses: usize = 0;
+    for line in reader.lines() {
+        let line = line.expect("Failed to read line from file.");
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("c") {
+            // Skip the comments and blank lines
+            continue;
+        } else if line.starts_with("p cnf") {
+            // Parse the header
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() != 4 {
+                panic!("Parse error! Unexpected header: {}", line)
+            }
+            num_vars = parts[2]
+                .parse()
+                .expect("Failed to parse number of variables.");
+            num_clauses = parts[3]
+                .parse()
+                .expect("Failed to parse number of clauses.");
+        } else {
+            // Parse a clause
+            let mut clause: Vec<Lit> = vec![];
+            let mut needed_vars = 0;
+            for lit_str in line.split_whitespace() {
+                let word: i32 = lit_str.parse().expect("Failed to parse literal");
+                if word == 0 {
+                    break;
+                }
+                let var = word.abs() - 1;
+                needed_vars = needed_vars.max(var as usize + 1);
+                clause.push(Lit::new(Var::from(var), word < 0));
+            }
+            add_clause(clause, needed_vars);
+            num_found_clauses += 1;
+        }
+    }
+
+    if num_found_clauses != num_clauses {
+        panic!("Error! DIMACS header mismatch: wrong number of clauses.");
+    }
+    num_vars
+}
+
+fn check_num_vars(found: usize, declared: usize) {
+    if found != declared {
+        panic!(
+            "Error! DIMACS header mismatch: wrong number of variables. {} {}",
+            found, declared
+        );
+    }
+}
+
+impl Solver {
     pub fn load_dimacs<T: BufRead>(&mut self, reader: T) {
-        let mut num_vars: usize = 0;
-        let mut num_clauses: usize = 0;
-        let mut num_found_clauses: usize = 0;
+        let num_vars = parse_dimacs(reader, |clause, needed_vars| {
+            while needed_vars > self.num_vars() {
+                self.new_var(true, true);
+            }
+            self.add_clause(clause);
+        });
+        check_num_vars(self.num_vars(), num_vars);
+    }
+
+    /// Writes the clauses that are not satisfied at the top level, without
+    /// their false literals, and the assumptions as unit clauses. Variables
+    /// are renumbered in the order they appear.
+    pub fn write_dimacs<W: Write>(&self, mut out: W, assumps: &[Lit]) -> io::Result<()> {
+        // Handle case when solver is in contradictory state:
+        if !self.ok {
+            return write!(out, "p cnf 1 2\n1 0\n-1 0\n");
+        }
+
+        let mut map: Vec<Option<usize>> = vec![None; self.num_vars()];
+        let mut max = 0;
+        let mut map_var = |x: Var| {
+            *map[x.into_usize()].get_or_insert_with(|| {
+                max += 1;
+                max
+            })
+        };
+        let mut dimacs = |p: Lit| {
+            let x = map_var(p.var()) as i64;
+            if p.sign() { -x } else { x }
+        };
+
+        let clauses: Vec<Vec<i64>> = self
+            .clauses
+            .iter()
+            .map(|&cr| &self.ca[cr])
+            .filter(|c| !self.satisfied(c))
+            .map(|c| {
+                c.iter()
+                    .filter(|p| self.lit_value(p) != LBool::False)
+                    .map(|&p| dimacs(p))
+                    .collect()
+            })
+            .collect();
+        let assumps: Vec<i64> = assumps.iter().map(|&p| dimacs(p)).collect();
+
+        writeln!(out, "p cnf {} {}", max, clauses.len() + assumps.len())?;
+        for x in assumps {
+            writeln!(out, "{} 0", x)?;
+        }
+        for c in clauses {
+            for x in c {
+                write!(out, "{} ", x)?;
+            }
+            writeln!(out, "0")?;
+        }
+        Ok(())
+    }
+
+    /// Reads assumptions as whitespace separated DIMACS literals, optionally
+    /// terminated by 0. Comment lines are skipped. Every literal must refer to
+    /// an existing variable.
+    pub fn load_assumptions<T: BufRead>(&self, reader: T) -> Vec<Lit> {
+        let mut assumptions: Vec<Lit> = vec![];
         for line in reader.lines() {
             let line = line.expect("Failed to read line from file.");
             let line = line.trim();
             if line.starts_with("c") {
-                // Skip the ecomments
                 continue;
-            } else if line.starts_with("p cnf") {
-                // Parse the header
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() != 4 {
-                    panic!("Parse error! Unexpected header: {}", line)
+            }
+            for lit_str in line.split_whitespace() {
+                let word: i32 = lit_str.parse().expect("Failed to parse literal");
+                if word == 0 {
+                    return assumptions;
                 }
-                num_vars = parts[2]
-                    .parse()
-                    .expect("Failed to parse number of variables.");
-                num_clauses = parts[3]
-                    .parse()
-                    .expect("Failed to parse number of clauses.");
-            } else {
-                // Parse a clause
-                let mut clause: Vec<Lit> = vec![];
-                for lit_str in line.split_whitespace() {
-                    let word: i32 = lit_str.parse().expect("Failed to parse literal");
-                    if word == 0 {
-                        break;
-                    }
-                    clause.push(Lit::from(word));
+                let var = word.abs() - 1;
+                if var as usize >= self.num_vars() {
+                    panic!("Error! Assumption on unknown variable: {}", word);
                 }
-                self.add_clause(clause);
-                num_found_clauses += 1;
+                assumptions.push(Lit::new(Var::from(var), word < 0));
             }
         }
+        assumptions
+    }
+}
 
-        if self.num_vars() != num_vars {
-            panic!(
-                "Error! DIMACS header mismatch: wrong number of variables. {} {}",
-                self.num_vars(),
-                num_vars
-            );
-        }
-        if num_found_clauses != num_clauses {
-            panic!("Error! DIMACS header mismatch: wrong number of clauses.");
-        }
+impl SimpSolver {
+    pub fn load_dimacs<T: BufRead>(&mut self, reader: T) {
+        let num_vars = parse_dimacs(reader, |clause, needed_vars| {
+            while needed_vars > self.num_vars() {
+                self.new_var(true, true);
+            }
+            self.add_clause(clause);
+        });
+        check_num_vars(self.num_vars(), num_vars);
     }
 }
 
@@ -143,4 +252,80 @@ mod tests {
         let mut solver = Solver::new();
         solver.load_dimacs(reader);
     }
+
+    #[test]
+    fn test_load_assumptions() {
+        let dimacs_data = "\
+        c This is a comment
+        1 -3
+        2 0
+        3
+        ";
+        let mut solver = Solver::new();
+        for _ in 0..3 {
+            solver.new_var(true, true);
+        }
+        let assumptions = solver.load_assumptions(BufReader::new(Cursor::new(dimacs_data)));
+
+        assert_eq!(
+            assumptions,
+            vec![
+                Lit::new(Var::from(0), false),
+                Lit::new(Var::from(2), true),
+                Lit::new(Var::from(1), false),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Error! Assumption on unknown variable: -4")]
+    fn test_load_assumptions_unknown_var() {
+        let mut solver = Solver::new();
+        solver.new_var(true, true);
+        solver.load_assumptions(BufReader::new(Cursor::new("1 -4 0")));
+    }
+
+    #[test]
+    fn test_write_dimacs() {
+        let dimacs_data = "\
+        p cnf 4 3
+        1 0
+        -1 3 4 0
+        1 2 0
+        ";
+        let mut solver = Solver::new();
+        solver.load_dimacs(BufReader::new(Cursor::new(dimacs_data)));
+        let mut out = vec![];
+        solver
+            .write_dimacs(&mut out, &[Lit::new(Var::from(3), true)])
+            .unwrap();
+
+        // x1 is true: the last clause is gone and x3 and x4 are renumbered.
+        assert_eq!(String::from_utf8(out).unwrap(), "p cnf 2 2\n-2 0\n1 2 0\n");
+    }
+
+    #[test]
+    fn test_write_dimacs_when_unsatisfiable() {
+        let mut solver = Solver::new();
+        solver.load_dimacs(BufReader::new(Cursor::new("p cnf 1 2\n1 0\n-1 0\n")));
+        let mut out = vec![];
+        solver.write_dimacs(&mut out, &[]).unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), "p cnf 1 2\n1 0\n-1 0\n");
+    }
+
+    #[test]
+    fn test_simp_solver_load_dimacs() {
+        let dimacs_data = "\
+        p cnf 3 2
+        1 -3 0
+        2 3 -1 0
+        ";
+        let mut simp = SimpSolver::new();
+        simp.load_dimacs(BufReader::new(Cursor::new(dimacs_data)));
+
+        assert_eq!(simp.num_vars(), 3);
+        assert_eq!(simp.frozen.len(), 3);
+        assert_eq!(simp.clauses.len(), 2);
+    }
 }
@@ -0,0 +1,421 @@
+//! Bounded variable elimination (Eén and Biere, SAT 2005). A variable is
+//! eliminated by replacing its clauses with all their resolvents on it, as
+//! long as that adds at most `grow` clauses and no resolvent is longer than
+//! `clause_lim`. The removed clauses are pushed on the reconstruction stack
+//! to extend the model. The resolvents are checked for backward subsumption
+//! as they are added.
+
+use crate::models::{clause::CRef, lbool::LBool, lit::Lit, simp_solver::SimpSolver, var::Var};
+
+impl SimpSolver {
+    /// Substitutes equivalent literals, then removes subsumed clauses and
+    /// eliminates variables until no more can be eliminated. With
+    /// `turn_off_elim`, the preprocessing is turned off for good afterwards.
+    /// Returns false if the clause set was found unsatisfiable.
+    pub fn eliminate(&mut self, turn_off_elim: bool) -> bool {
+        if !self.solver.simplify() {
+            return false;
+        } else if !self.use_simplification {
+            return true;
+        } else if self.use_equiv && !self.substitute_equivalences() {
+            return false;
+        }
+
+        self.build_occurs();
+        let eliminated_vars = self.eliminated_vars;
+        'elim: while self.n_touched > 0
+            || !self.subsumption_queue.is_empty()
+            || self.bwdsub_assigns < self.solver.trail.len()
+            || !self.elim_heap.is_empty()
+        {
+            self.gather_touched();
+            if !self.backward_subsumption_check() {
+                self.solver.ok = false;
+                break;
+            }
+
+            while !self.elim_heap.is_empty() {
+                let v = Var::from(self.elim_heap.remove_min(&self.elim_cost));
+                if self.solver.asynch_interrupt {
+                    break 'elim;
+                }
+                if self.is_eliminated(v)
+                    || self.frozen[v.into_usize()]
+                    || v.value(&self.solver.assigns) != LBool::Undefined
+                {
+                    continue;
+                }
+                if self.use_elim && !self.eliminate_var(v) {
+                    self.solver.ok = false;
+                    break 'elim;
+                }
+            }
+        }
+
+        if self.eliminated_vars > eliminated_vars {
+            self.remove_eliminated_learnts();
+        }
+
+        // The occurrence lists are not kept up to date by the search, which
+        // moves clauses in garbage collections:
+        for v in 0..self.solver.num_vars() {
+            self.occurs[Var::from(v)].clear();
+        }
+        self.elim_heap.build(&[], &self.elim_cost);
+        self.subsumption_queue.clear();
+        self.touched.fill(false);
+        self.n_touched = 0;
+        self.solver.ca.free(self.bwdsub_tmpunit);
+        self.bwdsub_tmpunit = CRef::UNDEF;
+        let ca = &self.solver.ca;
+        self.solver.clauses.retain(|&cr| ca[cr].mark() != 1);
+
+        if turn_off_elim {
+            self.use_simplification = false;
+            self.solver.ca.extra_clause_field = false;
+            // Force full cleanup (this is safe and desirable since it only happens once):
+            self.solver.rebuild_order_heap();
+            self.solver.garbage_collect();
+        } else {
+            self.solver.check_garbage();
+        }
+        self.solver.ok
+    }
+
+    /// Fills the occurrence lists with the original clauses, queues them for
+    /// subsumption and puts the variables that may be eliminated into the
+    /// elimination heap.
+    fn build_occurs(&mut self) {
+        self.n_occ.fill(0);
+        for &cr in self.solver.clauses.iter() {
+            for &p in self.solver.ca[cr].iter() {
+                self.occurs[p.var()].push(cr);
+                self.n_occ[p.into_usize()] += 1;
+            }
+        }
+        self.subsumption_queue.extend(self.solver.clauses.iter());
+        self.bwdsub_assigns = 0;
+        self.bwdsub_tmpunit = self.solver.ca.alloc(&[Lit::UNDEF], false);
+
+        let mut vars = vec![];
+        for v in (0..self.solver.num_vars()).map(Var::from) {
+            self.elim_cost[v.into_usize()] = self.resolution_cost(v);
+            if self.may_eliminate(v) {
+                vars.push(v.into_usize());
+            }
+        }
+        self.elim_heap.build(&vars, &self.elim_cost);
+    }
+
+    /// Removes the learnt clauses of earlier solves that contain an
+    /// eliminated variable.
+    fn remove_eliminated_learnts(&mut self) {
+        let crefs: Vec<CRef> = self
+            .solver
+            .learnts_core
+            .iter()
+            .chain(self.solver.learnts_tier2.iter())
+            .chain(self.solver.learnts_local.iter())
+            .copied()
+            .collect();
+        for cr in crefs {
+            if self.solver.ca[cr].mark() != 1
+                && self.solver.ca[cr]
+                    .iter()
+                    .any(|p| self.is_eliminated(p.var()))
+            {
+                self.solver.remove_clause(cr);
+            }
+        }
+        let ca = &self.solver.ca;
+        self.solver.learnts_core.retain(|&cr| ca[cr].mark() != 1);
+        self.solver.learnts_tier2.retain(|&cr| ca[cr].mark() != 1);
+        self.solver.learnts_local.retain(|&cr| ca[cr].mark() != 1);
+    }
+
+    fn may_eliminate(&self, v: Var) -> bool {
+        !self.frozen[v.into_usize()]
+            && !self.is_eliminated(v)
+            && v.value(&self.solver.assigns) == LBool::Undefined
+    }
+
+    /// The negated number of resolutions needed to eliminate `v`.
+    fn resolution_cost(&self, v: Var) -> f64 {
+        let pos = self.n_occ[Lit::new(v, false).into_usize()];
+        let neg = self.n_occ[Lit::new(v, true).into_usize()];
+        -(pos as f64 * neg as f64)
+    }
+
+    /// Restores the position of `v` in the elimination heap after its number
+    /// of occurrences changed.
+    pub fn update_elim_heap(&mut self, v: Var) {
+        let x = v.into_usize();
+        let old_cost = self.elim_cost[x];
+        self.elim_cost[x] = self.resolution_cost(v);
+        if self.elim_heap.in_heap(x) {
+            if self.elim_cost[x] > old_cost {
+                self.elim_heap.decrease(x, &self.elim_cost);
+            } else if self.elim_cost[x] < old_cost {
+                self.elim_heap.increase(x, &self.elim_cost);
+            }
+        } else if self.may_eliminate(v) {
+            self.elim_heap.insert(x, &self.elim_cost);
+        }
+    }
+
+    /// Removes the original clause, keeping the occurrences up to date.
+    pub fn remove_clause(&mut self, cr: CRef) {
+        for k in 0..self.solver.ca[cr].size() {
+            let p = self.solver.ca[cr][k];
+            self.n_occ[p.into_usize()] -= 1;
+            self.update_elim_heap(p.var());
+            self.occurs.smudge(p.var());
+        }
+        self.solver.remove_clause(cr);
+    }
+
+    /// Adds the clause `resolvent` to the original clauses and queues it for
+    /// subsumption.
+    fn add_resolvent(&mut self) -> bool {
+        let num_clauses = self.solver.clauses.len();
+        if !self.solver.add_clause(self.resolvent.clone()) {
+            return false;
+        }
+        if self.solver.clauses.len() == num_clauses + 1 {
+            let cr = *self.solver.clauses.last().unwrap();
+            for k in 0..self.solver.ca[cr].size() {
+                let p = self.solver.ca[cr][k];
+                self.occurs[p.var()].push(cr);
+                self.n_occ[p.into_usize()] += 1;
+                self.update_elim_heap(p.var());
+                if !self.touched[p.var().into_usize()] {
+                    self.touched[p.var().into_usize()] = true;
+                    self.n_touched += 1;
+                }
+            }
+            self.subsumption_queue.push_back(cr);
+        }
+        true
+    }
+
+    /// Eliminates `v` unless that grows the clause set too much, then checks
+    /// the resolvents for backward subsumption. Returns false if the clause
+    /// set was found unsatisfiable.
+    fn eliminate_var(&mut self, v: Var) -> bool {
+        assert!(!self.frozen[v.into_usize()] && !self.is_eliminated(v));
+
+        // Split the occurrences into positive and negative:
+        let ca = &self.solver.ca;
+        self.occurs.clean(v, |&cr| ca[cr].mark() == 1);
+        let cls = self.occurs[v].clone();
+        let (pos, neg): (Vec<CRef>, Vec<CRef>) = cls
+            .iter()
+            .partition(|&&cr| self.solver.ca[cr].iter().any(|&p| p == Lit::new(v, false)));
+
+        // Check whether the increase in number of clauses stays within the
+        // allowed ('grow'). Moreover, no clause must exceed the limit on the
+        // maximal clause size (if it is set):
+        let mut cnt = 0;
+        for &pr in pos.iter() {
+            for &nr in neg.iter() {
+                if self.merge(pr, nr, v) {
+                    cnt += 1;
+                    if cnt > cls.len() as i32 + self.grow
+                        || (self.clause_lim != -1
+                            && self.resolvent.len() > self.clause_lim as usize)
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        // Delete and store old clauses:
+        self.eliminated[v.into_usize()] = true;
+        self.solver.set_decision_var(v, false);
+        self.eliminated_vars += 1;
+
+        if pos.len() > neg.len() {
+            for &cr in neg.iter() {
+                self.mk_elim_clause(v, cr);
+            }
+            self.mk_elim_unit(Lit::new(v, false));
+        } else {
+            for &cr in pos.iter() {
+                self.mk_elim_clause(v, cr);
+            }
+            self.mk_elim_unit(Lit::new(v, true));
+        }
+        for &cr in cls.iter() {
+            self.remove_clause(cr);
+        }
+
+        // Produce clauses in cross product:
+        for &pr in pos.iter() {
+            for &nr in neg.iter() {
+                if self.merge(pr, nr, v) && !self.add_resolvent() {
+                    return false;
+                }
+            }
+        }
+
+        self.occurs[v].clear();
+        self.backward_subsumption_check()
+    }
+
+    /// Stores the clause for model extension, witnessed by the literal of
+    /// `v`.
+    fn mk_elim_clause(&mut self, v: Var, cr: CRef) {
+        let c = &self.solver.ca[cr];
+        let p = *c.iter().find(|p| p.var() == v).unwrap();
+        self.solver.reconstruction.push(&[p], c.lits());
+    }
+
+    /// Stores the default value of an eliminated variable, for when none of
+    /// its stored clauses need it.
+    fn mk_elim_unit(&mut self, x: Lit) {
+        self.solver.reconstruction.push(&[x], &[x]);
+    }
+
+    /// Resolves the clauses on `v` into `resolvent`. Returns false if the
+    /// resolvent is a tautology.
+    fn merge(&mut self, pr: CRef, nr: CRef, v: Var) -> bool {
+        self.merges += 1;
+        self.resolvent.clear();
+
+        let (ps, qs) = (&self.solver.ca[pr], &self.solver.ca[nr]);
+        let (ps, qs) = if ps.size() < qs.size() {
+            (qs, ps)
+        } else {
+            (ps, qs)
+        };
+        for &q in qs.iter().filter(|q| q.var() != v) {
+            match ps.iter().find(|p| p.var() == q.var()) {
+                Some(&p) if p == !q => return false,
+                Some(_) => {}
+                None => self.resolvent.push(q),
+            }
+        }
+        self.resolvent
+            .extend(ps.iter().filter(|p| p.var() != v).copied());
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{clause, lit, simp_with_clauses, sorted_clauses};
+
+    #[test]
+    fn test_merge() {
+        let mut simp = simp_with_clauses(4, &[&[1, 2, 3], &[-1, 3, 4], &[-1, -2]]);
+        let x1 = lit(1).var();
+        let cs = simp.clauses.clone();
+
+        assert!(simp.merge(cs[0], cs[1], x1));
+        simp.resolvent.sort();
+        assert_eq!(simp.resolvent, clause(&[2, 3, 4]));
+        // Resolving on x1 leaves x2 in both polarities:
+        assert!(!simp.merge(cs[0], cs[2], x1));
+        assert_eq!(simp.merges, 2);
+    }
+
+    #[test]
+    fn test_eliminate_replaces_clauses_by_resolvents() {
+        let mut simp = simp_with_clauses(5, &[&[1, 2], &[1, 3], &[-1, 4], &[-1, 5]]);
+        for v in [2, 3, 4, 5] {
+            simp.set_frozen(lit(v).var(), true);
+        }
+
+        assert!(simp.eliminate(false));
+        assert!(simp.is_eliminated(lit(1).var()));
+        assert!(!simp.decision[0]);
+        assert_eq!(
+            sorted_clauses(&simp, &simp.clauses),
+            vec![
+                clause(&[2, 4]),
+                clause(&[2, 5]),
+                clause(&[3, 4]),
+                clause(&[3, 5])
+            ]
+        );
+        // The smaller side is stored, followed by the default value:
+        assert_eq!(simp.reconstruction.len(), 2 + 1);
+    }
+
+    #[test]
+    fn test_eliminate_removes_learnt_clauses_of_eliminated_vars() {
+        let mut simp = simp_with_clauses(3, &[&[1, 2], &[-1, 3]]);
+        simp.set_frozen(lit(2).var(), true);
+        simp.set_frozen(lit(3).var(), true);
+        // Learnt clauses left behind by an earlier solve:
+        for c in [&[-1, 2, 3][..], &[2, 3]] {
+            let cr = simp.ca.alloc(&clause(c), true);
+            simp.attach_clause(cr);
+            simp.learnts_local.push(cr);
+        }
+
+        assert!(simp.eliminate(false));
+        assert!(simp.is_eliminated(lit(1).var()));
+        assert_eq!(
+            sorted_clauses(&simp, &simp.learnts_local),
+            vec![clause(&[2, 3])]
+        );
+    }
+
+    #[test]
+    fn test_eliminate_respects_grow_limit() {
+        // Eliminating x1 would replace 6 clauses by 9.
+        let mut simp = simp_with_clauses(
+            7,
+            &[&[1, 2], &[1, 3], &[1, 4], &[-1, 5], &[-1, 6], &[-1, 7]],
+        );
+        for v in 2..=7 {
+            simp.set_frozen(lit(v).var(), true);
+        }
+
+        assert!(simp.eliminate(false));
+        assert!(!simp.is_eliminated(lit(1).var()));
+        assert_eq!(simp.clauses.len(), 6);
+
+        simp.grow = 3;
+        assert!(simp.eliminate(false));
+        assert!(simp.is_eliminated(lit(1).var()));
+        assert_eq!(simp.clauses.len(), 9);
+    }
+
+    #[test]
+    fn test_eliminate_respects_clause_limit() {
+        let mut simp = simp_with_clauses(5, &[&[1, 2, 3], &[-1, 4, 5]]);
+        for v in 2..=5 {
+            simp.set_frozen(lit(v).var(), true);
+        }
+        simp.clause_lim = 3;
+
+        assert!(simp.eliminate(false));
+        assert!(!simp.is_eliminated(lit(1).var()));
+
+        simp.clause_lim = 4;
+        assert!(simp.eliminate(false));
+        assert!(simp.is_eliminated(lit(1).var()));
+    }
+
+    #[test]
+    fn test_eliminate_finds_unsatisfiability() {
+        let mut simp = simp_with_clauses(2, &[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+
+        assert!(!simp.eliminate(true));
+        assert!(!simp.ok);
+    }
+
+    #[test]
+    fn test_turn_off_elim() {
+        let mut simp = simp_with_clauses(3, &[&[1, 2], &[-2, 3]]);
+
+        assert!(simp.eliminate(true));
+        assert!(!simp.use_simplification);
+        let eliminated = simp.eliminated_vars;
+        assert!(simp.eliminate(true));
+        assert_eq!(simp.eliminated_vars, eliminated);
+    }
+}
@@ -0,0 +1,349 @@
+//! Equivalent literal substitution. The strongly connected components of the
+//! binary implication graph, found by Tarjan's algorithm, are classes of
+//! equivalent literals. Each class is replaced by a representative in every
+//! original and learnt clause, and the substituted variables are eliminated
+//! with their equivalences pushed on the reconstruction stack.
+
+use crate::models::{clause::CRef, lbool::LBool, lit::Lit, simp_solver::SimpSolver, var::Var};
+
+const UNVISITED: usize = usize::MAX;
+
+impl SimpSolver {
+    /// Substitutes the equivalent literals found in the binary clauses.
+    /// Frozen variables are kept, and preferred as representatives. Returns
+    /// false if a literal was found equivalent to its negation, or the
+    /// clause set otherwise unsatisfiable.
+    pub fn substitute_equivalences(&mut self) -> bool {
+        assert!(self.solver.decision_level() == 0);
+        if !self.solver.ok || self.solver.propagate() != CRef::UNDEF {
+            self.solver.ok = false;
+            return false;
+        }
+
+        let repr = match self.find_equivalences() {
+            Some(repr) => repr,
+            None => {
+                self.solver.ok = false;
+                return false;
+            }
+        };
+        let substituted: Vec<Var> = (0..self.solver.num_vars())
+            .map(Var::from)
+            .filter(|&v| repr[Lit::new(v, false).into_usize()] != Lit::UNDEF)
+            .collect();
+        if substituted.is_empty() {
+            return true;
+        }
+
+        // Top-level assignments need no reasons, and their reason clauses may
+        // change below:
+        for i in 0..self.solver.trail.len() {
+            let v = self.solver.trail[i].var();
+            self.solver.vardata[v.into_usize()].reason = CRef::UNDEF;
+        }
+
+        let mut units = vec![];
+        let crefs: Vec<CRef> = self
+            .solver
+            .clauses
+            .iter()
+            .chain(self.solver.learnts_core.iter())
+            .chain(self.solver.learnts_tier2.iter())
+            .chain(self.solver.learnts_local.iter())
+            .copied()
+            .collect();
+        for cr in crefs {
+            if self.solver.ca[cr].mark() != 1 && !self.substitute_clause(cr, &repr, &mut units) {
+                self.solver.ok = false;
+                return false;
+            }
+        }
+        let ca = &self.solver.ca;
+        self.solver.clauses.retain(|&cr| ca[cr].mark() != 1);
+        self.solver.learnts_core.retain(|&cr| ca[cr].mark() != 1);
+        self.solver.learnts_tier2.retain(|&cr| ca[cr].mark() != 1);
+        self.solver.learnts_local.retain(|&cr| ca[cr].mark() != 1);
+
+        // The values of the substituted variables follow their representatives:
+        for v in substituted {
+            let p = Lit::new(v, false);
+            let r = repr[p.into_usize()];
+            self.solver.reconstruction.push(&[p], &[p, !r]);
+            self.solver.reconstruction.push(&[!p], &[!p, r]);
+            self.eliminated[v.into_usize()] = true;
+            self.solver.set_decision_var(v, false);
+            self.substituted_vars += 1;
+        }
+
+        for p in units {
+            match p.value(&self.solver.assigns) {
+                LBool::True => {}
+                LBool::False => self.solver.ok = false,
+                LBool::Undefined => self.solver.unchecked_enqueue(p, CRef::UNDEF),
+            }
+        }
+        if !self.solver.ok || self.solver.propagate() != CRef::UNDEF {
+            self.solver.ok = false;
+        }
+        self.solver.check_garbage();
+        self.solver.ok
+    }
+
+    /// Finds the strongly connected components of the binary implication
+    /// graph over the unassigned literals of variables not eliminated, and
+    /// returns the representative of
+    /// every substituted literal, or `Lit::UNDEF`. Returns `None` if a literal
+    /// is equivalent to its negation.
+    fn find_equivalences(&mut self) -> Option<Vec<Lit>> {
+        let ca = &self.solver.ca;
+        self.solver
+            .watches_bin
+            .clean_all(|w| ca[w.cref].mark() == 1);
+
+        let num_lits = 2 * self.solver.num_vars();
+        let mut repr = vec![Lit::UNDEF; num_lits];
+        let mut index = vec![UNVISITED; num_lits];
+        let mut low = vec![0; num_lits];
+        let mut on_stack = vec![false; num_lits];
+        let mut stack: Vec<Lit> = vec![];
+        let mut calls: Vec<(Lit, usize)> = vec![]; // The literals being visited, with their next watcher.
+        let mut done = vec![false; self.solver.num_vars()]; // Variables of the classes already handled.
+        let mut counter = 0;
+
+        for root in (0..num_lits).map(|x| Lit::from(x as i32)) {
+            if index[root.into_usize()] != UNVISITED
+                || root.value(&self.solver.assigns) != LBool::Undefined
+                || self.is_eliminated(root.var())
+            {
+                continue;
+            }
+            index[root.into_usize()] = counter;
+            low[root.into_usize()] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root.into_usize()] = true;
+            calls.push((root, 0));
+
+            while let Some(&(p, i)) = calls.last() {
+                if i < self.solver.watches_bin[p].len() {
+                    calls.last_mut().unwrap().1 += 1;
+                    let q = self.solver.watches_bin[p][i].blocker;
+                    if q.value(&self.solver.assigns) != LBool::Undefined
+                        || self.is_eliminated(q.var())
+                    {
+                        continue;
+                    }
+                    if index[q.into_usize()] == UNVISITED {
+                        index[q.into_usize()] = counter;
+                        low[q.into_usize()] = counter;
+                        counter += 1;
+                        stack.push(q);
+                        on_stack[q.into_usize()] = true;
+                        calls.push((q, 0));
+                    } else if on_stack[q.into_usize()] {
+                        low[p.into_usize()] = low[p.into_usize()].min(index[q.into_usize()]);
+                    }
+                    continue;
+                }
+
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    low[parent.into_usize()] = low[parent.into_usize()].min(low[p.into_usize()]);
+                }
+                if low[p.into_usize()] != index[p.into_usize()] {
+                    continue;
+                }
+                // 'p' is the root of a component:
+                let mut class = vec![];
+                loop {
+                    let q = stack.pop().unwrap();
+                    on_stack[q.into_usize()] = false;
+                    class.push(q);
+                    if q == p {
+                        break;
+                    }
+                }
+                // Every class has a mirror class of the negations, handled
+                // together with it:
+                if class.len() == 1 || done[class[0].var().into_usize()] {
+                    continue;
+                }
+                class.sort();
+                if class.windows(2).any(|w| w[0].var() == w[1].var()) {
+                    return None;
+                }
+                let r = class
+                    .iter()
+                    .find(|q| self.frozen[q.var().into_usize()])
+                    .copied()
+                    .unwrap_or(class[0]);
+                for &q in class.iter() {
+                    done[q.var().into_usize()] = true;
+                    if q != r && !self.frozen[q.var().into_usize()] {
+                        repr[q.into_usize()] = r;
+                        repr[(!q).into_usize()] = !r;
+                    }
+                }
+            }
+        }
+        Some(repr)
+    }
+
+    /// Replaces the substituted literals of the clause by their
+    /// representatives, removing duplicates and the literals false at the top
+    /// level. The clause is removed if it becomes satisfied or a tautology,
+    /// or a unit, which is added to `units`. Returns false if it becomes
+    /// empty.
+    fn substitute_clause(&mut self, cr: CRef, repr: &[Lit], units: &mut Vec<Lit>) -> bool {
+        if self.solver.ca[cr]
+            .iter()
+            .all(|p| repr[p.into_usize()] == Lit::UNDEF)
+        {
+            return true;
+        }
+        self.solver.detach_clause(cr, true);
+
+        let mut lits: Vec<Lit> = self.solver.ca[cr]
+            .iter()
+            .map(|&p| match repr[p.into_usize()] {
+                Lit::UNDEF => p,
+                r => r,
+            })
+            .collect();
+        lits.sort();
+        lits.dedup();
+        let satisfied = lits.windows(2).any(|w| w[0] == !w[1])
+            || lits
+                .iter()
+                .any(|p| p.value(&self.solver.assigns) == LBool::True);
+        lits.retain(|p| p.value(&self.solver.assigns) == LBool::Undefined);
+
+        if satisfied || lits.len() < 2 {
+            self.solver.ca[cr].set_mark(1);
+            self.solver.ca.free(cr);
+            if !satisfied {
+                match lits.first() {
+                    Some(&p) => units.push(p),
+                    None => return false,
+                }
+            }
+            return true;
+        }
+
+        let old_size = self.solver.ca[cr].size();
+        self.solver.ca[cr].lits_mut()[..lits.len()].copy_from_slice(&lits);
+        self.solver.ca.shrink(cr, old_size - lits.len());
+        let c = &mut self.solver.ca[cr];
+        if c.has_extra() && !c.learnt() {
+            c.calc_abstraction();
+        }
+        self.solver.attach_clause(cr);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{clause, lit, satisfies, simp_with_clauses, sorted_clauses};
+
+    #[test]
+    fn test_substitutes_representative() {
+        let mut simp = simp_with_clauses(
+            5,
+            &[
+                &[-1, 2],
+                &[1, -2],
+                &[-2, 3],
+                &[-3, 1],
+                &[2, 4, 5],
+                &[-3, -4, 5],
+            ],
+        );
+
+        assert!(simp.substitute_equivalences());
+        assert_eq!(simp.substituted_vars, 2);
+        assert!(simp.is_eliminated(lit(2).var()));
+        assert!(simp.is_eliminated(lit(3).var()));
+        assert!(!simp.decision[1]);
+        assert_eq!(
+            sorted_clauses(&simp, &simp.clauses),
+            vec![clause(&[1, 4, 5]), clause(&[-1, -4, 5])]
+        );
+        assert_eq!(simp.reconstruction.len(), 4);
+    }
+
+    #[test]
+    fn test_substitutes_learnt_clauses() {
+        let mut simp = simp_with_clauses(4, &[&[-1, 2], &[1, -2], &[-2, 1, 4]]);
+        let cr = simp.ca.alloc(&clause(&[2, 1, 3]), true);
+        simp.attach_clause(cr);
+        simp.learnts_local.push(cr);
+
+        assert!(simp.substitute_equivalences());
+        // The tautology is removed and the duplicate literal merged:
+        assert!(simp.clauses.is_empty());
+        assert_eq!(
+            sorted_clauses(&simp, &simp.learnts_local),
+            vec![clause(&[1, 3])]
+        );
+        // The clauses may have moved in a garbage collection:
+        let cr = simp.learnts_local[0];
+        assert!(simp.watches_bin[lit(-1)].iter().any(|w| w.cref == cr));
+    }
+
+    #[test]
+    fn test_frozen_variables_are_kept() {
+        let mut simp = simp_with_clauses(2, &[&[-1, 2], &[1, -2]]);
+        simp.set_frozen(lit(2).var(), true);
+
+        assert!(simp.substitute_equivalences());
+        assert!(simp.is_eliminated(lit(1).var()));
+        assert!(!simp.is_eliminated(lit(2).var()));
+    }
+
+    #[test]
+    fn test_eliminated_variables_are_skipped() {
+        // Left behind by an earlier solve, the learnt clauses make x1 and x2
+        // equivalent, but x1 was eliminated since:
+        let mut simp = simp_with_clauses(3, &[&[2, 3]]);
+        for c in [[-1, 2], [1, -2]] {
+            let cr = simp.ca.alloc(&clause(&c), true);
+            simp.attach_clause(cr);
+            simp.learnts_local.push(cr);
+        }
+        simp.eliminated[0] = true;
+
+        assert!(simp.substitute_equivalences());
+        assert_eq!(simp.substituted_vars, 0);
+        assert!(!simp.is_eliminated(lit(2).var()));
+        assert!(simp.reconstruction.is_empty());
+    }
+
+    #[test]
+    fn test_equivalent_to_negation_is_unsatisfiable() {
+        let mut simp = simp_with_clauses(3, &[&[-1, 2], &[-2, -1], &[1, 3], &[-3, 1]]);
+
+        assert!(!simp.substitute_equivalences());
+        assert!(!simp.ok);
+    }
+
+    #[test]
+    fn test_solve_extends_model_to_substituted_vars() {
+        let clauses: &[&[i32]] = &[
+            &[-1, 2],
+            &[1, -2],
+            &[-2, -3],
+            &[2, 3],
+            &[3, 4, 5],
+            &[-1, -4],
+            &[-5, 1],
+        ];
+        let mut simp = simp_with_clauses(5, clauses);
+        simp.use_elim = false;
+
+        assert_eq!(simp.solve(), LBool::True);
+        assert_eq!(simp.substituted_vars, 2);
+        assert!(satisfies(&simp.model, clauses));
+    }
+}
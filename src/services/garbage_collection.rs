@@ -0,0 +1,130 @@
+use crate::models::{clause::CRef, clause_allocator::ClauseAllocator, solver::Solver};
+
+impl Solver {
+    /// Moves all live clauses into `to`, updating every reference to them.
+    fn reloc_all(&mut self, to: &mut ClauseAllocator) {
+        // All watchers:
+        let ca = &self.ca;
+        self.watches.clean_all(|w| ca[w.cref].mark() == 1);
+        self.watches_bin.clean_all(|w| ca[w.cref].mark() == 1);
+        for ws in self.watches.lists_mut().chain(self.watches_bin.lists_mut()) {
+            for w in ws.iter_mut() {
+                self.ca.reloc(&mut w.cref, to);
+            }
+        }
+
+        // All reasons:
+        for i in 0..self.trail.len() {
+            let v = self.trail[i].var();
+            let reason = self.reason(v);
+            if reason != CRef::UNDEF && (self.ca[reason].reloced() || self.locked(reason)) {
+                self.ca.reloc(&mut self.vardata[v.into_usize()].reason, to);
+            }
+        }
+
+        // All learnt:
+        let learnts = self
+            .learnts_core
+            .iter_mut()
+            .chain(self.learnts_tier2.iter_mut())
+            .chain(self.learnts_local.iter_mut());
+        for cr in learnts {
+            self.ca.reloc(cr, to);
+        }
+
+        // All original:
+        for cr in self.clauses.iter_mut() {
+            self.ca.reloc(cr, to);
+        }
+    }
+
+    pub fn garbage_collect(&mut self) {
+        // Initialize the next region to a size corresponding to the estimated
+        // utilization degree. This is not precise but should avoid some
+        // unnecessary reallocations for the new region:
+        let mut to = ClauseAllocator::with_capacity(self.ca.size() - self.ca.wasted());
+        to.extra_clause_field = self.ca.extra_clause_field;
+
+        self.reloc_all(&mut to);
+        if self.verbosity >= 2 {
+            println!(
+                "|  Garbage collection:   {:12} bytes => {:12} bytes             |",
+                self.ca.size() * 4,
+                to.size() * 4
+            );
+        }
+        self.ca = to;
+    }
+
+    /// Collects garbage once the wasted memory exceeds `garbage_frac`.
+    pub fn check_garbage(&mut self) {
+        if self.ca.wasted() as f64 > self.ca.size() as f64 * self.garbage_frac {
+            self.garbage_collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::lbool::LBool,
+        test_utils::{lit, solver_with_vars},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_simplify_triggers_garbage_collection() {
+        let mut solver = solver_with_vars(5);
+        assert!(solver.add_clause(vec![lit(1), lit(2)]));
+        assert!(solver.add_clause(vec![lit(1), lit(3), lit(4)]));
+        assert!(solver.add_clause(vec![lit(-2), lit(5)]));
+        assert!(solver.add_clause(vec![lit(3), lit(-4), lit(-5)]));
+        assert_eq!(solver.ca.size(), 14);
+
+        // Satisfies the first two clauses, which make up half of the memory.
+        assert!(solver.add_clause(vec![lit(1)]));
+        assert!(solver.simplify());
+
+        assert_eq!(solver.clauses.len(), 2);
+        assert_eq!(solver.ca.wasted(), 0);
+        assert_eq!(solver.ca.size(), 7);
+        assert_eq!(solver.ca[solver.clauses[0]].lits(), &[lit(-2), lit(5)]);
+        assert_eq!(
+            solver.ca[solver.clauses[1]].lits(),
+            &[lit(3), lit(-4), lit(-5)]
+        );
+
+        // The relocated watchers still propagate.
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(2), CRef::UNDEF);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(solver.lit_value(&lit(5)), LBool::True);
+        assert_eq!(solver.lit_value(&lit(-4)), LBool::Undefined);
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(-3), CRef::UNDEF);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(solver.lit_value(&lit(-4)), LBool::True);
+    }
+
+    #[test]
+    fn test_garbage_collect_relocates_reasons() {
+        let mut solver = solver_with_vars(4);
+        assert!(solver.add_clause(vec![lit(3), lit(4)]));
+        assert!(solver.add_clause(vec![lit(-1), lit(2)]));
+        solver.remove_clause(solver.clauses[0]);
+        solver.clauses.remove(0);
+
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(1), CRef::UNDEF);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        let reason = solver.reason(lit(2).var());
+        assert_eq!(reason, solver.clauses[0]);
+
+        solver.garbage_collect();
+        let reason = solver.reason(lit(2).var());
+        assert_eq!(reason, CRef(0));
+        assert_eq!(solver.clauses[0], reason);
+        assert!(solver.locked(reason));
+    }
+}
@@ -0,0 +1,272 @@
+//! Learnt clause minimization (Luo et al., IJCAI 2017). At restarts, the
+//! core and tier2 clauses are vivified: the negations of their literals are
+//! decided one at a time, and literals implied false are dropped. Once the
+//! decisions lead to a conflict or imply a remaining literal, the clause
+//! shrinks to the literals involved.
+
+use crate::models::{
+    clause::{CORE, CRef, TIER2},
+    lbool::LBool,
+    lit::Lit,
+    solver::Solver,
+};
+
+impl Solver {
+    /// Minimizes every core and tier2 clause that was not minimized yet.
+    /// Returns false if the clause set was found unsatisfiable.
+    pub fn minimize_learnts(&mut self) -> bool {
+        assert!(self.decision_level() == 0);
+        self.lcm_runs += 1;
+        self.next_lcm = self.conflicts + self.lcm_interval + self.lcm_inc * self.lcm_runs;
+        // The decisions of the pass would overwrite the saved phases:
+        let polarity = self.polarity.clone();
+
+        for mark in [CORE, TIER2] {
+            let mut cs = match mark {
+                CORE => std::mem::take(&mut self.learnts_core),
+                _ => std::mem::take(&mut self.learnts_tier2),
+            };
+            let mut j = 0;
+            for i in 0..cs.len() {
+                let cr = cs[i];
+                // Skip the clauses that moved to another tier:
+                if self.ca[cr].mark() != mark {
+                    continue;
+                }
+                if !self.ok {
+                    cs[j] = cr;
+                    j += 1;
+                } else if self.satisfied(&self.ca[cr]) {
+                    self.remove_clause(cr);
+                } else if self.ca[cr].simplified() || self.minimize_learnt(cr) {
+                    cs[j] = cr;
+                    j += 1;
+                }
+            }
+            cs.truncate(j);
+            match mark {
+                CORE => self.learnts_core = cs,
+                _ => self.learnts_tier2 = cs,
+            }
+        }
+
+        self.polarity = polarity;
+        self.check_garbage();
+        self.ok
+    }
+
+    /// Minimizes the learnt clause, which must not be satisfied at the top
+    /// level. Returns false if the clause was removed, because it became a
+    /// unit or satisfied at the top level.
+    pub fn minimize_learnt(&mut self, cr: CRef) -> bool {
+        self.detach_clause(cr, true);
+        let old_size = self.ca[cr].size();
+        let lits: Vec<Lit> = self.ca[cr]
+            .iter()
+            .filter(|p| self.lit_value(p) == LBool::Undefined)
+            .copied()
+            .collect();
+
+        // Decide the negations until a conflict or a true literal:
+        let trail_record = self.trail.len();
+        let mut vivified = vec![];
+        let mut confl = CRef::UNDEF;
+        let mut implied = Lit::UNDEF;
+        for &p in lits.iter() {
+            self.lcm_tested_lits += 1;
+            match self.lit_value(&p) {
+                LBool::Undefined => {
+                    vivified.push(p);
+                    self.new_decision_level();
+                    self.unchecked_enqueue(!p, CRef::UNDEF);
+                    confl = self.propagate();
+                    if confl != CRef::UNDEF {
+                        break;
+                    }
+                }
+                LBool::True => {
+                    vivified.push(p);
+                    implied = p;
+                    confl = self.reason(p.var());
+                    break;
+                }
+                // Implied false by the previous decisions:
+                LBool::False => {}
+            }
+        }
+        if confl != CRef::UNDEF {
+            vivified = self.lcm_analyze(confl, implied, trail_record);
+        }
+        self.cancel_until(0);
+        if self.propagate() != CRef::UNDEF {
+            self.ok = false;
+        }
+
+        // Top-level units may have been found on the way:
+        if vivified.iter().any(|p| self.lit_value(p) == LBool::True) {
+            self.ca[cr].set_mark(1);
+            self.ca.free(cr);
+            return false;
+        }
+        vivified.retain(|p| self.lit_value(p) == LBool::Undefined);
+        self.lcm_removed_lits += (old_size - vivified.len()) as u64;
+
+        match vivified.len() {
+            0 => {
+                self.ok = false;
+                self.ca[cr].set_mark(1);
+                self.ca.free(cr);
+                false
+            }
+            1 => {
+                self.ca[cr].set_mark(1);
+                self.ca.free(cr);
+                self.unchecked_enqueue(vivified[0], CRef::UNDEF);
+                if self.propagate() != CRef::UNDEF {
+                    self.ok = false;
+                }
+                false
+            }
+            n => {
+                self.ca[cr].lits_mut()[..n].copy_from_slice(&vivified);
+                self.ca.shrink(cr, old_size - n);
+                let c = &mut self.ca[cr];
+                c.set_simplified(true);
+                #[cfg(feature = "lbd_based_clause_deletion")]
+                if c.lbd() as usize > n {
+                    c.set_lbd(n as u32);
+                }
+                self.attach_clause(cr);
+                true
+            }
+        }
+    }
+
+    /// Returns the literals of the clause being minimized whose negations
+    /// were decided and led to the conflict `confl`, or that imply the true
+    /// literal `implied` through its reason `confl`, together with `implied`.
+    fn lcm_analyze(&mut self, confl: CRef, implied: Lit, trail_record: usize) -> Vec<Lit> {
+        let mut out = vec![];
+        let start = if implied == Lit::UNDEF {
+            0
+        } else {
+            out.push(implied);
+            1
+        };
+        for k in start..self.ca[confl].size() {
+            let q = self.ca[confl][k];
+            if self.level(q.var()) > 0 {
+                self.seen[q.var().into_usize()] = 1;
+            }
+        }
+
+        for i in (trail_record..self.trail.len()).rev() {
+            let x = self.trail[i].var();
+            if self.seen[x.into_usize()] == 0 {
+                continue;
+            }
+            self.seen[x.into_usize()] = 0;
+            let reason = self.reason(x);
+            if reason == CRef::UNDEF {
+                out.push(!self.trail[i]);
+            } else {
+                for k in 1..self.ca[reason].size() {
+                    let q = self.ca[reason][k];
+                    if self.level(q.var()) > 0 {
+                        self.seen[q.var().into_usize()] = 1;
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{clause, lit, pigeonhole, slices, solver_with_clauses, sorted};
+
+    fn add_core(solver: &mut Solver, dimacs: &[i32]) -> CRef {
+        let cr = solver.ca.alloc(&clause(dimacs), true);
+        solver.ca[cr].set_mark(CORE);
+        solver.attach_clause(cr);
+        solver.learnts_core.push(cr);
+        cr
+    }
+
+    #[test]
+    fn test_drops_literal_implied_false() {
+        // -x1 implies -x2, so x2 is redundant in (x1 x2 x3).
+        let mut solver = solver_with_clauses(3, &[&[1, -2]]);
+        let cr = add_core(&mut solver, &[1, 2, 3]);
+
+        assert!(solver.minimize_learnt(cr));
+        assert_eq!(sorted(solver.ca[cr].lits()), clause(&[1, 3]));
+        assert!(solver.ca[cr].simplified());
+        assert_eq!(solver.decision_level(), 0);
+        assert_eq!(solver.lcm_removed_lits, 1);
+        // The clause is watched again, as a binary clause now.
+        assert!(solver.watches_bin[lit(-1)].iter().any(|w| w.cref == cr));
+        assert!(!solver.watches[lit(-1)].iter().any(|w| w.cref == cr));
+        assert!(!solver.watches[lit(-2)].iter().any(|w| w.cref == cr));
+    }
+
+    #[test]
+    fn test_shrinks_to_conflict_decisions() {
+        // -x1 and -x2 conflict by themselves, x3 and x4 are not needed.
+        let mut solver = solver_with_clauses(5, &[&[1, 2, 5], &[1, 2, -5]]);
+        let cr = add_core(&mut solver, &[1, 3, 2, 4]);
+
+        assert!(solver.minimize_learnt(cr));
+        assert_eq!(sorted(solver.ca[cr].lits()), clause(&[1, 2]));
+        assert!(solver.seen.iter().all(|&s| s == 0));
+    }
+
+    #[test]
+    fn test_shrinks_to_implied_literal() {
+        // -x1 implies x3, so (x1 x3) subsumes the clause.
+        let mut solver = solver_with_clauses(4, &[&[1, 3]]);
+        let cr = add_core(&mut solver, &[1, 2, 3, 4]);
+
+        assert!(solver.minimize_learnt(cr));
+        assert_eq!(sorted(solver.ca[cr].lits()), clause(&[1, 3]));
+    }
+
+    #[test]
+    fn test_unit_is_enqueued() {
+        // -x1 alone leads to a conflict: x1 is implied.
+        let mut solver = solver_with_clauses(3, &[&[1, 2], &[1, -2]]);
+        add_core(&mut solver, &[1, 3]);
+
+        solver.minimize_learnts();
+        assert!(solver.learnts_core.is_empty());
+        assert_eq!(solver.lit_value(&lit(1)), LBool::True);
+        assert_eq!(solver.level(lit(1).var()), 0);
+        assert!(solver.ok);
+    }
+
+    #[test]
+    fn test_minimized_clauses_are_skipped() {
+        let mut solver = solver_with_clauses(3, &[&[1, -2]]);
+        add_core(&mut solver, &[1, 2, 3]);
+
+        solver.minimize_learnts();
+        solver.minimize_learnts();
+        assert_eq!(solver.lcm_tested_lits, 3);
+        assert_eq!(solver.lcm_runs, 2);
+        assert_eq!(solver.learnts_core.len(), 1);
+    }
+
+    #[test]
+    fn test_solve_with_lcm() {
+        let (num_vars, clauses) = pigeonhole(6, 5);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+        solver.lcm_interval = 10;
+        solver.lcm_inc = 10;
+        solver.restart_first = 5;
+
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.lcm_runs > 1);
+    }
+}
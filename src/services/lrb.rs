@@ -0,0 +1,244 @@
+//! Learning-Rate Branching (Liang et al., SAT 2016). The activity of a
+//! variable is an exponential moving average of its learning rate: the
+//! fraction of the conflicts it participated in while it was assigned.
+
+use crate::models::{branching_heuristic::BranchingHeuristic, heap::Heap, lrb::Lrb};
+
+impl Lrb {
+    /// Decays the activity of a variable for every conflict that happened
+    /// since it was unassigned, during which its learning rate would have
+    /// been zero.
+    fn decay_unpicked(
+        &mut self,
+        v: usize,
+        conflicts: u64,
+        activity: &mut [f64],
+        order_heap: &mut Heap,
+    ) {
+        let age = conflicts - self.canceled[v];
+        if age > 0 {
+            activity[v] *= 0.95f64.powi(age as i32);
+            if order_heap.in_heap(v) {
+                order_heap.increase(v, activity);
+            }
+        }
+    }
+}
+
+impl BranchingHeuristic for Lrb {
+    fn new_var(&mut self) {
+        self.picked.push(0);
+        self.conflicted.push(0);
+        self.almost_conflicted.push(0);
+        self.canceled.push(0);
+    }
+
+    /// Starts a new interval of the variable: it is now assigned and has not
+    /// participated in any conflict yet.
+    fn on_assign(&mut self, v: usize, conflicts: u64, activity: &mut [f64], order_heap: &mut Heap) {
+        self.picked[v] = conflicts;
+        if self.anti_exploration {
+            self.decay_unpicked(v, conflicts, activity, order_heap);
+        }
+        self.conflicted[v] = 0;
+        self.almost_conflicted[v] = 0;
+    }
+
+    /// Closes the interval of the variable and moves its activity towards the
+    /// learning rate observed in that interval.
+    fn on_unassign(
+        &mut self,
+        v: usize,
+        conflicts: u64,
+        activity: &mut [f64],
+        order_heap: &mut Heap,
+    ) {
+        let age = conflicts - self.picked[v];
+        if age > 0 {
+            let mut participated = self.conflicted[v];
+            if self.almost_conflict {
+                participated += self.almost_conflicted[v];
+            }
+            let reward = participated as f64 / age as f64;
+            let old_activity = activity[v];
+            activity[v] = self.step_size * reward + (1.0 - self.step_size) * old_activity;
+            if order_heap.in_heap(v) {
+                if activity[v] > old_activity {
+                    order_heap.decrease(v, activity);
+                } else {
+                    order_heap.increase(v, activity);
+                }
+            }
+        }
+        self.canceled[v] = conflicts;
+    }
+
+    fn on_conflict_var(
+        &mut self,
+        v: usize,
+        _conflicts: u64,
+        _activity: &mut [f64],
+        _order_heap: &mut Heap,
+    ) {
+        self.conflicted[v] += 1;
+    }
+
+    fn wants_reason_vars(&self) -> bool {
+        self.almost_conflict
+    }
+
+    fn on_reason_var(&mut self, v: usize) {
+        self.almost_conflicted[v] += 1;
+    }
+
+    fn on_conflict(&mut self) {
+        if self.step_size > self.min_step_size {
+            self.step_size -= self.step_size_dec;
+        }
+    }
+
+    /// Decays the top of the heap for the conflicts it was not picked in,
+    /// until the top is up to date.
+    fn before_pick(&mut self, conflicts: u64, activity: &mut [f64], order_heap: &mut Heap) {
+        if !self.anti_exploration {
+            return;
+        }
+        let mut v = order_heap[0];
+        while conflicts > self.canceled[v] {
+            self.decay_unpicked(v, conflicts, activity, order_heap);
+            self.canceled[v] = conflicts;
+            v = order_heap[0];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{branching_heuristic::Branching, clause::CRef, lbool::LBool, solver::Solver},
+        test_utils::{lit, solver_with_branching},
+    };
+
+    use super::*;
+
+    fn lrb_with_vars(n: usize) -> (Lrb, Vec<f64>, Heap) {
+        let mut lrb = Lrb::default();
+        let activity = vec![0.0; n];
+        let mut order_heap = Heap::new();
+        for v in 0..n {
+            lrb.new_var();
+            order_heap.insert(v, &activity);
+        }
+        (lrb, activity, order_heap)
+    }
+
+    #[test]
+    fn test_unassign_rewards_learning_rate() {
+        let (mut lrb, mut activity, mut order_heap) = lrb_with_vars(2);
+        lrb.on_assign(0, 0, &mut activity, &mut order_heap);
+
+        // Participated in 2 of the 4 conflicts while assigned.
+        lrb.conflicted[0] = 2;
+        lrb.on_unassign(0, 4, &mut activity, &mut order_heap);
+
+        assert!((activity[0] - 0.4 * 0.5).abs() < 1e-12);
+        assert_eq!(activity[1], 0.0);
+        assert_eq!(order_heap[0], 0);
+    }
+
+    #[test]
+    fn test_unassign_without_conflicts_keeps_activity() {
+        let mut solver = solver_with_branching(Branching::Lrb, 1, &[]);
+        solver.activity[0] = 0.5;
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(1), CRef::UNDEF);
+        solver.cancel_until(0);
+
+        assert_eq!(solver.activity[0], 0.5);
+    }
+
+    #[test]
+    fn test_assign_decays_unpicked_activity() {
+        let (mut lrb, mut activity, mut order_heap) = lrb_with_vars(1);
+        lrb.anti_exploration = true;
+        activity[0] = 1.0;
+        lrb.on_assign(0, 2, &mut activity, &mut order_heap);
+
+        assert!((activity[0] - 0.95 * 0.95).abs() < 1e-12);
+        assert_eq!(lrb.picked[0], 2);
+    }
+
+    #[test]
+    fn test_analyze_counts_conflict_participation() {
+        // Level 1: x1 -> x2 -> x4. Level 2: x3 -> x5 -> conflict on (-3 -5 -1 -4).
+        let lrb = Lrb {
+            almost_conflict: true,
+            anti_exploration: false,
+            ..Lrb::default()
+        };
+        let mut solver = Solver::new().with_heuristic(Box::new(lrb));
+        for _ in 0..5 {
+            solver.new_var(true, true);
+        }
+        for c in [&[-1, 2][..], &[-2, 4], &[-3, 5], &[-3, -5, -1, -4]] {
+            assert!(solver.add_clause(c.iter().map(|&x| lit(x)).collect()));
+        }
+        solver.ccmin_mode = 0;
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(1), CRef::UNDEF);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(3), CRef::UNDEF);
+        let confl = solver.propagate();
+        assert_ne!(confl, CRef::UNDEF);
+
+        let mut learnt = vec![];
+        solver.analyze(confl, &mut learnt);
+        assert!(solver.seen.iter().all(|&s| s == 0));
+
+        // Every variable was assigned for one conflict. x3, x5, x1 and x4 are
+        // in the conflict, x2 is only in the reason of x4.
+        solver.conflicts = 1;
+        solver.cancel_until(0);
+        assert!(solver.activity.iter().all(|&a| (a - 0.4).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_step_size_decays_per_conflict() {
+        let mut lrb = Lrb {
+            step_size_dec: 0.01,
+            ..Lrb::default()
+        };
+        for _ in 0..100 {
+            lrb.on_conflict();
+        }
+        let mut expected = 0.4;
+        for _ in 0..100 {
+            if expected > 0.06 {
+                expected -= 0.01;
+            }
+        }
+        assert!((lrb.step_size - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_solve_with_lrb() {
+        let mut solver = solver_with_branching(
+            Branching::Lrb,
+            6,
+            &[
+                &[1, 2],
+                &[3, 4],
+                &[5, 6],
+                &[-1, -3],
+                &[-1, -5],
+                &[-3, -5],
+                &[-2, -4],
+                &[-2, -6],
+                &[-4, -6],
+            ],
+        );
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.conflicts > 0);
+    }
+}
@@ -1,2 +1,21 @@
+mod branching;
+mod chb;
+mod clause_deletion;
+mod conflict_analysis;
 mod dimacs;
+mod elimination;
+mod equivalence;
+mod garbage_collection;
+mod learnt_minimization;
+mod lrb;
+mod mode;
+mod probing;
+mod propagation;
+mod rephase;
+mod restart;
+mod search;
+mod simp_solver;
 mod solver;
+mod subsumption;
+mod vsids;
+mod walk;
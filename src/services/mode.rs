@@ -0,0 +1,163 @@
+//! Mode switching: the search alternates between a focused mode, with
+//! frequent Glucose restarts, and a stable mode, with rare Luby or geometric
+//! restarts. The modes are given geometrically growing numbers of conflicts,
+//! and each mode keeps its own branching heuristic and variable activities.
+
+use crate::models::{
+    mode::{Mode, ModeStats},
+    restart::Restart,
+    solver::Solver,
+};
+
+impl Solver {
+    /// The restart policy of the current mode.
+    pub fn restart_policy(&self) -> Restart {
+        if !self.mode_switching {
+            return self.restart;
+        }
+        match self.mode {
+            Mode::Focused => Restart::Glucose,
+            Mode::Stable if self.restart == Restart::Glucose => Restart::Luby,
+            Mode::Stable => self.restart,
+        }
+    }
+
+    /// The initial restart limit of the current mode.
+    pub fn restart_first(&self) -> i32 {
+        if self.mode_switching && self.mode == Mode::Stable {
+            self.stable_restart_first
+        } else {
+            self.restart_first
+        }
+    }
+
+    pub fn mode_switch_due(&self) -> bool {
+        self.mode_switching && self.conflicts >= self.next_mode_switch
+    }
+
+    /// Starts counting the conflicts of the first mode.
+    pub fn init_mode_switching(&mut self) {
+        if self.next_mode_switch == 0 {
+            self.mode_length = self.mode_switch_first as f64;
+            self.next_mode_switch = self.conflicts + self.mode_switch_first;
+        }
+        self.mode_start = self.mode_totals();
+    }
+
+    /// Switches to the other mode. Must be called at decision level 0.
+    pub fn switch_mode(&mut self) {
+        assert!(self.decision_level() == 0);
+        self.account_mode();
+        self.mode = match self.mode {
+            Mode::Focused => Mode::Stable,
+            Mode::Stable => {
+                self.mode_length *= self.mode_switch_inc;
+                Mode::Focused
+            }
+        };
+        self.next_mode_switch = self.conflicts + self.mode_length as u64;
+        self.mode_switches += 1;
+
+        std::mem::swap(&mut self.heuristic, &mut self.other_heuristic);
+        std::mem::swap(&mut self.activity, &mut self.other_activity);
+        self.rebuild_order_heap();
+    }
+
+    /// Adds the statistics since the start of the current mode to it.
+    pub fn account_mode(&mut self) {
+        let totals = self.mode_totals();
+        let stats = match self.mode {
+            Mode::Focused => &mut self.focused_stats,
+            Mode::Stable => &mut self.stable_stats,
+        };
+        stats.conflicts += totals.conflicts - self.mode_start.conflicts;
+        stats.decisions += totals.decisions - self.mode_start.decisions;
+        stats.propagations += totals.propagations - self.mode_start.propagations;
+        stats.starts += totals.starts - self.mode_start.starts;
+        self.mode_start = totals;
+    }
+
+    fn mode_totals(&self) -> ModeStats {
+        ModeStats {
+            conflicts: self.conflicts,
+            decisions: self.decisions,
+            propagations: self.propagations,
+            starts: self.starts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{branching_heuristic::Branching, lbool::LBool},
+        test_utils::{pigeonhole, slices, solver_with_clauses},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_restart_policy_per_mode() {
+        let mut solver = Solver::new();
+        assert_eq!(solver.restart_policy(), Restart::Luby);
+        solver.mode_switching = true;
+        assert_eq!(solver.restart_policy(), Restart::Glucose);
+        assert_eq!(solver.restart_first(), 100);
+
+        solver.mode = Mode::Stable;
+        assert_eq!(solver.restart_policy(), Restart::Luby);
+        assert_eq!(solver.restart_first(), 1000);
+        solver.restart = Restart::Geometric;
+        assert_eq!(solver.restart_policy(), Restart::Geometric);
+    }
+
+    #[test]
+    fn test_switch_swaps_heuristic_state() {
+        let mut solver = Solver::new()
+            .with_branching(Branching::Vsids)
+            .with_stable_branching(Branching::Chb);
+        for _ in 0..3 {
+            solver.new_var(true, true);
+        }
+        solver.mode_switching = true;
+        solver.init_mode_switching();
+        solver.activity[2] = 1.0;
+        solver.conflicts = 1000;
+        solver.decisions = 5;
+
+        solver.switch_mode();
+        assert_eq!(solver.mode, Mode::Stable);
+        assert_eq!(solver.activity, vec![0.0; 3]);
+        assert_eq!(solver.other_activity, vec![0.0, 0.0, 1.0]);
+        assert_eq!(solver.next_mode_switch, 2000);
+        assert_eq!(solver.focused_stats.conflicts, 1000);
+        assert_eq!(solver.focused_stats.decisions, 5);
+
+        solver.conflicts = 2000;
+        solver.switch_mode();
+        assert_eq!(solver.mode, Mode::Focused);
+        assert_eq!(solver.order_heap[0], 2);
+        assert_eq!(solver.next_mode_switch, 4000);
+        assert_eq!(solver.stable_stats.conflicts, 1000);
+        assert_eq!(solver.mode_switches, 2);
+    }
+
+    #[test]
+    fn test_solve_with_mode_switching() {
+        let (num_vars, clauses) = pigeonhole(7, 6);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+        solver.mode_switching = true;
+        solver.mode_switch_first = 100;
+
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.mode_switches > 0);
+        assert_eq!(
+            solver.focused_stats.conflicts + solver.stable_stats.conflicts,
+            solver.conflicts
+        );
+        assert_eq!(
+            solver.focused_stats.starts + solver.stable_stats.starts,
+            solver.starts
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Failed literal probing on the binary implication graph. At the top level,
+//! a literal is decided and propagated: if that fails, its negation is a
+//! unit. A root of the graph, a literal with implications but no binary
+//! clause implying it, is probed alone. The other variables with
+//! implications are probed in both polarities, and the literals implied by
+//! both are units. Literals a probe implies through longer clauses get
+//! hyper-binary resolvents, so later probes and the search find them by
+//! binary propagation.
+
+use std::collections::HashSet;
+
+#[cfg(feature = "lbd_based_clause_deletion")]
+use crate::models::clause::TIER2;
+use crate::models::{clause::CRef, lbool::LBool, lit::Lit, solver::Solver, var::Var};
+
+/// The outcome of probing a literal.
+enum Probe {
+    Failed,
+    Implied(Vec<Lit>),
+}
+
+impl Solver {
+    /// Probes the variables in the binary implication graph, starting where
+    /// the last round stopped, until `probe_budget` propagations were made.
+    /// Returns false if the clause set was found unsatisfiable.
+    pub fn probe(&mut self) -> bool {
+        assert!(self.decision_level() == 0);
+        self.probe_runs += 1;
+        self.next_probe = self.conflicts + self.probe_interval * self.probe_runs;
+        self.probe_hbr_end = self.hbr_resolvents + self.probe_hbr_limit;
+        if !self.ok || self.propagate() != CRef::UNDEF {
+            self.ok = false;
+            return false;
+        }
+        // The decisions of the round would overwrite the saved phases:
+        let polarity = self.polarity.clone();
+
+        let ca = &self.ca;
+        self.watches_bin.clean_all(|w| ca[w.cref].mark() == 1);
+        let budget_end = self.propagations as i64 + self.probe_budget;
+        let num_vars = self.num_vars();
+        for _ in 0..num_vars {
+            if !self.ok
+                || self.asynch_interrupt
+                || (self.probe_budget >= 0 && self.propagations as i64 >= budget_end)
+            {
+                break;
+            }
+            let v = Var::from(self.probe_next_var);
+            self.probe_next_var = (self.probe_next_var + 1) % num_vars;
+            if !self.decision[v.into_usize()] || v.value(&self.assigns) != LBool::Undefined {
+                continue;
+            }
+
+            // A literal has implications if its negation is in a binary clause:
+            let p = Lit::new(v, false);
+            match (
+                self.watches_bin[p].is_empty(),
+                self.watches_bin[!p].is_empty(),
+            ) {
+                (false, true) => self.probe_root(p),
+                (true, false) => self.probe_root(!p),
+                (false, false) => self.probe_both(p),
+                (true, true) => {}
+            }
+        }
+
+        self.polarity = polarity;
+        self.check_garbage();
+        self.ok
+    }
+
+    /// Probes a root, learning its negation if it fails.
+    fn probe_root(&mut self, p: Lit) {
+        if let Probe::Failed = self.probe_lit(p) {
+            self.failed_literals += 1;
+            self.enqueue_unit(!p);
+        }
+    }
+
+    /// Probes both polarities of the variable of `p`, learning the negation
+    /// of a failed one or the literals both imply.
+    fn probe_both(&mut self, p: Lit) {
+        let implied = match self.probe_lit(p) {
+            Probe::Failed => {
+                self.failed_literals += 1;
+                self.enqueue_unit(!p);
+                return;
+            }
+            Probe::Implied(implied) => implied,
+        };
+        // 'seen' holds 1 for the variables implied true by 'p', 2 for false:
+        for q in implied.iter() {
+            self.seen[q.var().into_usize()] = 1 + q.sign() as u8;
+        }
+        let units = match self.probe_lit(!p) {
+            Probe::Failed => {
+                self.failed_literals += 1;
+                vec![p]
+            }
+            Probe::Implied(neg_implied) => {
+                let units: Vec<Lit> = neg_implied
+                    .into_iter()
+                    .filter(|q| self.seen[q.var().into_usize()] == 1 + q.sign() as u8)
+                    .collect();
+                self.probe_units += units.len() as u64;
+                units
+            }
+        };
+        for q in implied.iter() {
+            self.seen[q.var().into_usize()] = 0;
+        }
+        for q in units {
+            if self.ok && self.lit_value(&q) == LBool::Undefined {
+                self.enqueue_unit(q);
+            }
+        }
+    }
+
+    /// Decides `p` at level 1 and propagates it. Unless that fails, returns
+    /// the implied literals and adds a hyper-binary resolvent for those
+    /// implied by longer clauses, but not already by binary clauses.
+    fn probe_lit(&mut self, p: Lit) -> Probe {
+        self.probed_lits += 1;
+        let trail_start = self.trail.len();
+        self.new_decision_level();
+        self.unchecked_enqueue(p, CRef::UNDEF);
+        if self.propagate() != CRef::UNDEF {
+            self.cancel_until(0);
+            return Probe::Failed;
+        }
+
+        let implied: Vec<Lit> = self.trail[trail_start + 1..].to_vec();
+        let mut hyper: Vec<Lit> = implied
+            .iter()
+            .filter(|q| self.ca[self.reason(q.var())].size() > 2)
+            .copied()
+            .collect();
+        if !hyper.is_empty() {
+            let by_binaries = self.binary_implied(p);
+            hyper.retain(|q| !by_binaries.contains(q));
+        }
+        self.cancel_until(0);
+        for q in hyper {
+            if self.hbr_resolvents >= self.probe_hbr_end {
+                break;
+            }
+            let cr = self.ca.alloc(&[q, !p], true);
+            self.attach_clause(cr);
+            self.add_hyper_binary(cr);
+            self.hbr_resolvents += 1;
+        }
+        Probe::Implied(implied)
+    }
+
+    /// The literals `p` implies through binary clauses alone.
+    fn binary_implied(&self, p: Lit) -> HashSet<Lit> {
+        let mut implied = HashSet::new();
+        let mut stack = vec![p];
+        while let Some(q) = stack.pop() {
+            for w in self.watches_bin[q].iter() {
+                if self.ca[w.cref].mark() != 1 && implied.insert(w.blocker) {
+                    stack.push(w.blocker);
+                }
+            }
+        }
+        implied
+    }
+
+    /// Adds a hyper-binary resolvent to the learnt clauses. The levels are
+    /// stale after backtracking, so the LBD is set to 2 instead of computed,
+    /// and the clause goes to tier2 rather than the core.
+    fn add_hyper_binary(&mut self, cr: CRef) {
+        #[cfg(feature = "lbd_based_clause_deletion")]
+        {
+            self.ca[cr].set_lbd(2);
+            self.ca[cr].set_mark(TIER2);
+            self.ca[cr].set_touched(self.conflicts as u32);
+            self.learnts_tier2.push(cr);
+        }
+        #[cfg(not(feature = "lbd_based_clause_deletion"))]
+        self.add_learnt(cr);
+    }
+
+    /// Adds a unit found by probing and propagates it.
+    fn enqueue_unit(&mut self, p: Lit) {
+        self.unchecked_enqueue(p, CRef::UNDEF);
+        if self.propagate() != CRef::UNDEF {
+            self.ok = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{clause, lit, solver_with_clauses, sorted_clauses};
+
+    fn learnts(solver: &Solver) -> Vec<Vec<Lit>> {
+        let crs: Vec<CRef> = [
+            &solver.learnts_core,
+            &solver.learnts_tier2,
+            &solver.learnts_local,
+        ]
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect();
+        sorted_clauses(solver, &crs)
+    }
+
+    #[test]
+    fn test_failed_root_is_learnt() {
+        // Probing x1 implies x2 and x3, and then a conflict:
+        let mut solver = solver_with_clauses(4, &[&[-1, 2], &[-1, 3], &[-2, -3, 4], &[-2, -3, -4]]);
+
+        assert!(solver.probe());
+        assert_eq!(solver.failed_literals, 1);
+        assert_eq!(lit(-1).value(&solver.assigns), LBool::True);
+        assert_eq!(solver.decision_level(), 0);
+    }
+
+    #[test]
+    fn test_literals_implied_by_both_polarities() {
+        let mut solver = solver_with_clauses(4, &[&[-1, 2], &[-2, 4], &[1, 3], &[-3, 4]]);
+
+        assert!(solver.probe());
+        assert_eq!(solver.failed_literals, 0);
+        assert_eq!(solver.probe_units, 1);
+        assert_eq!(lit(4).value(&solver.assigns), LBool::True);
+        assert_eq!(lit(1).value(&solver.assigns), LBool::Undefined);
+    }
+
+    #[test]
+    fn test_hyper_binary_resolution() {
+        let mut solver = solver_with_clauses(4, &[&[-1, 2], &[-1, 3], &[-2, -3, 4]]);
+
+        assert!(solver.probe());
+        assert_eq!(solver.hbr_resolvents, 1);
+        assert_eq!(learnts(&solver), vec![clause(&[-1, 4])]);
+        // The resolvent is propagated as a binary clause:
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(1), CRef::UNDEF);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(solver.ca[solver.reason(lit(4).var())].size(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "lbd_based_clause_deletion")]
+    fn test_hyper_binary_resolvents_go_to_tier2() {
+        let mut solver = solver_with_clauses(4, &[&[-1, 2], &[-1, 3], &[-2, -3, 4]]);
+
+        assert!(solver.probe());
+        assert!(solver.learnts_core.is_empty());
+        assert_eq!(solver.learnts_tier2.len(), 1);
+        assert_eq!(solver.ca[solver.learnts_tier2[0]].lbd(), 2);
+    }
+
+    #[test]
+    fn test_no_resolvent_for_literals_implied_by_binaries() {
+        // Probing x1 implies x3 by the longer clause first, and by x2 too:
+        let mut solver = solver_with_clauses(5, &[&[-1, 2], &[-1, 5], &[3, -1, -5], &[-2, 3]]);
+
+        assert!(solver.probe());
+        assert_eq!(solver.hbr_resolvents, 0);
+        assert!(learnts(&solver).is_empty());
+    }
+
+    #[test]
+    fn test_hyper_binary_resolvents_per_round_are_limited() {
+        let mut solver = solver_with_clauses(
+            6,
+            &[&[-1, 2], &[-1, 3], &[-2, -3, 4], &[-2, -3, 5], &[-2, -3, 6]],
+        );
+        solver.probe_hbr_limit = 2;
+
+        assert!(solver.probe());
+        assert_eq!(solver.hbr_resolvents, 2);
+        assert_eq!(learnts(&solver).len(), 2);
+    }
+
+    #[test]
+    fn test_probe_budget() {
+        let mut solver = solver_with_clauses(4, &[&[-1, 2], &[-1, 3], &[-2, -3, 4], &[-2, -3, -4]]);
+        solver.probe_budget = 0;
+
+        assert!(solver.probe());
+        assert_eq!(solver.probed_lits, 0);
+
+        solver.probe_budget = -1;
+        assert!(solver.probe());
+        assert_eq!(lit(-1).value(&solver.assigns), LBool::True);
+    }
+
+    #[test]
+    fn test_probing_finds_unsatisfiability() {
+        let mut solver = solver_with_clauses(2, &[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2]]);
+
+        assert!(!solver.probe());
+        assert!(!solver.ok);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::models::{clause::CRef, lbool::LBool, solver::Solver, watcher::Watcher};
+
+impl Solver {
+    /// Propagates all enqueued facts. If a conflict arises, the conflicting
+    /// clause is returned, otherwise `CRef::UNDEF`.
+    ///
+    /// Post-conditions: the propagation queue is empty, even if there was a
+    /// conflict.
+    pub fn propagate(&mut self) -> CRef {
+        let mut confl = CRef::UNDEF;
+        let mut num_props: u64 = 0;
+        let ca = &self.ca;
+        self.watches.clean_all(|w| ca[w.cref].mark() == 1);
+        self.watches_bin.clean_all(|w| ca[w.cref].mark() == 1);
+
+        while (self.qhead as usize) < self.trail.len() {
+            let p = self.trail[self.qhead as usize]; // 'p' is enqueued fact to propagate.
+            let curr_level = self.vardata[p.var().into_usize()].level;
+            self.qhead += 1;
+            num_props += 1;
+
+            // Propagate the binary clauses first, without visiting them:
+            let ws_bin = std::mem::take(&mut self.watches_bin[p]);
+            for w in ws_bin.iter() {
+                let the_other = w.blocker;
+                match the_other.value(&self.assigns) {
+                    LBool::False => {
+                        confl = w.cref;
+                        break;
+                    }
+                    LBool::Undefined => {
+                        // Reasons keep the implied literal first:
+                        let c = &mut self.ca[w.cref];
+                        if c[0] != the_other {
+                            c.swap(0, 1);
+                        }
+                        self.unchecked_enqueue_at(the_other, curr_level, w.cref);
+                    }
+                    LBool::True => {}
+                }
+            }
+            self.watches_bin[p] = ws_bin;
+            if confl != CRef::UNDEF {
+                self.qhead = self.trail.len() as i32;
+                break;
+            }
+
+            let mut ws = std::mem::take(&mut self.watches[p]);
+
+            let mut i = 0;
+            let mut j = 0;
+            'next_clause: while i < ws.len() {
+                // Try to avoid inspecting the clause:
+                let blocker = ws[i].blocker;
+                if blocker.value(&self.assigns) == LBool::True {
+                    ws[j] = ws[i];
+                    j += 1;
+                    i += 1;
+                    continue;
+                }
+
+                // Make sure the false literal is data[1]:
+                let cr = ws[i].cref;
+                let c = &mut self.ca[cr];
+                let false_lit = !p;
+                if c[0] == false_lit {
+                    c.swap(0, 1);
+                }
+                debug_assert!(c[1] == false_lit);
+                i += 1;
+
+                // If 0th watch is true, then clause is already satisfied.
+                let first = c[0];
+                let w = Watcher::new(cr, first);
+                if first != blocker && first.value(&self.assigns) == LBool::True {
+                    ws[j] = w;
+                    j += 1;
+                    continue;
+                }
+
+                // Look for new watch:
+                for k in 2..c.size() {
+                    if c[k].value(&self.assigns) != LBool::False {
+                        c.swap(1, k);
+                        self.watches[!c[1]].push(w);
+                        continue 'next_clause;
+                    }
+                }
+
+                // Did not find watch -- clause is unit under assignment:
+                ws[j] = w;
+                j += 1;
+                if first.value(&self.assigns) == LBool::False {
+                    confl = cr;
+                    self.qhead = self.trail.len() as i32;
+                    // Copy the remaining watches:
+                    while i < ws.len() {
+                        ws[j] = ws[i];
+                        j += 1;
+                        i += 1;
+                    }
+                } else if curr_level == self.trail_lim.len() as i32 {
+                    self.unchecked_enqueue_at(first, curr_level, cr);
+                } else {
+                    // 'p' was implied below the current decision level. The
+                    // implied literal belongs to the highest level in the
+                    // clause, which must then be watched:
+                    let mut max_level = curr_level;
+                    let mut max_ind = 1;
+                    for k in 2..c.size() {
+                        let level = self.vardata[c[k].var().into_usize()].level;
+                        if level > max_level {
+                            max_level = level;
+                            max_ind = k;
+                        }
+                    }
+                    if max_ind != 1 {
+                        c.swap(1, max_ind);
+                        j -= 1; // Undo last watch.
+                        self.watches[!c[1]].push(w);
+                    }
+                    self.unchecked_enqueue_at(first, max_level, cr);
+                }
+            }
+            ws.truncate(j);
+            self.watches[p] = ws;
+        }
+        self.heuristic.on_propagated(
+            confl != CRef::UNDEF,
+            self.conflicts,
+            &mut self.activity,
+            &mut self.order_heap,
+        );
+        self.propagations += num_props;
+        self.simp_db_props -= num_props as i64;
+
+        confl
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{lit::Lit, var::Var};
+
+    use super::*;
+
+    fn lits(solver: &mut Solver, dimacs: &[i32]) -> Vec<Lit> {
+        dimacs
+            .iter()
+            .map(|&x| {
+                let var = x.abs() - 1;
+                while var as usize >= solver.num_vars() {
+                    solver.new_var(true, true);
+                }
+                Lit::new(Var::from(var), x < 0)
+            })
+            .collect()
+    }
+
+    fn decide(solver: &mut Solver, dimacs: i32) {
+        let p = lits(solver, &[dimacs])[0];
+        solver.new_decision_level();
+        solver.unchecked_enqueue(p, CRef::UNDEF);
+    }
+
+    fn value(solver: &mut Solver, dimacs: i32) -> LBool {
+        let p = lits(solver, &[dimacs])[0];
+        solver.lit_value(&p)
+    }
+
+    #[test]
+    fn test_propagate_implication_chain() {
+        let mut solver = Solver::new();
+        let c1 = lits(&mut solver, &[-1, 2]);
+        let c2 = lits(&mut solver, &[-2, 3]);
+        assert!(solver.add_clause(c1));
+        assert!(solver.add_clause(c2));
+
+        decide(&mut solver, 1);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(value(&mut solver, 2), LBool::True);
+        assert_eq!(value(&mut solver, 3), LBool::True);
+        assert_eq!(solver.trail.len(), 3);
+        assert_eq!(solver.qhead as usize, solver.trail.len());
+    }
+
+    #[test]
+    fn test_propagate_returns_conflict() {
+        let mut solver = Solver::new();
+        let c1 = lits(&mut solver, &[-1, 2]);
+        let c2 = lits(&mut solver, &[-1, -2]);
+        assert!(solver.add_clause(c1));
+        assert!(solver.add_clause(c2));
+
+        decide(&mut solver, 1);
+        let confl = solver.propagate();
+        assert_ne!(confl, CRef::UNDEF);
+        assert!(
+            solver.ca[confl]
+                .iter()
+                .all(|lit| solver.lit_value(lit) == LBool::False)
+        );
+        assert_eq!(solver.qhead as usize, solver.trail.len());
+    }
+
+    #[test]
+    fn test_propagate_binary_clause() {
+        let mut solver = Solver::new();
+        let c = lits(&mut solver, &[2, -1]);
+        assert!(solver.add_clause(c));
+        let cr = solver.clauses[0];
+        let x1 = lits(&mut solver, &[1])[0];
+        assert_eq!(solver.watches_bin[x1].len(), 1);
+        assert!(solver.watches[x1].is_empty());
+
+        decide(&mut solver, 1);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(value(&mut solver, 2), LBool::True);
+        let x2 = lits(&mut solver, &[2])[0];
+        assert_eq!(solver.reason(x2.var()), cr);
+        // The implied literal comes first in its reason:
+        assert_eq!(solver.ca[cr][0], x2);
+    }
+
+    #[test]
+    fn test_propagate_moves_watch() {
+        let mut solver = Solver::new();
+        let c = lits(&mut solver, &[1, 2, 3]);
+        assert!(solver.add_clause(c));
+
+        decide(&mut solver, -1);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(value(&mut solver, 3), LBool::Undefined);
+        // The clause is now watched by the third literal instead of the first.
+        let neg3 = lits(&mut solver, &[-3])[0];
+        assert_eq!(solver.watches[neg3].len(), 1);
+
+        decide(&mut solver, -2);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(value(&mut solver, 3), LBool::True);
+    }
+
+    #[test]
+    fn test_propagate_skips_clause_with_true_blocker() {
+        let mut solver = Solver::new();
+        let c = lits(&mut solver, &[1, 2, 3]);
+        assert!(solver.add_clause(c));
+
+        decide(&mut solver, 2);
+        decide(&mut solver, -1);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        assert_eq!(value(&mut solver, 3), LBool::Undefined);
+    }
+
+    #[test]
+    fn test_propagate_below_decision_level() {
+        let mut solver = Solver::new();
+        let c = lits(&mut solver, &[-1, -2, 3]);
+        assert!(solver.add_clause(c));
+
+        decide(&mut solver, 1);
+        decide(&mut solver, 4);
+        // Imply x2 out of order, at the first level:
+        let p = lits(&mut solver, &[2])[0];
+        solver.unchecked_enqueue_at(p, 1, CRef::UNDEF);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+        let x3 = lits(&mut solver, &[3])[0].var();
+        assert_eq!(solver.level(x3), 1);
+
+        // Both stay assigned when the second level is canceled:
+        solver.cancel_until(1);
+        assert_eq!(value(&mut solver, 2), LBool::True);
+        assert_eq!(value(&mut solver, 3), LBool::True);
+        assert_eq!(value(&mut solver, 4), LBool::Undefined);
+        assert_eq!(solver.trail.len(), 3);
+        assert_eq!(solver.qhead, 1);
+    }
+
+    #[test]
+    fn test_propagate_watches_highest_level() {
+        let mut solver = Solver::new();
+        let c = lits(&mut solver, &[3, -2, 1, -4]);
+        assert!(solver.add_clause(c));
+
+        decide(&mut solver, 4);
+        decide(&mut solver, 5);
+        decide(&mut solver, -1);
+        let p = lits(&mut solver, &[2])[0];
+        solver.unchecked_enqueue_at(p, 1, CRef::UNDEF);
+        assert_eq!(solver.propagate(), CRef::UNDEF);
+
+        // x3 is implied at the level of -1, which becomes its other watch.
+        let x3 = lits(&mut solver, &[3])[0];
+        assert_eq!(solver.level(x3.var()), 3);
+        let cr = solver.reason(x3.var());
+        let not1 = lits(&mut solver, &[-1])[0];
+        assert_eq!(solver.ca[cr][1], !not1);
+        assert!(solver.watches[not1].iter().any(|w| w.cref == cr));
+        assert!(!solver.watches[p].iter().any(|w| w.cref == cr));
+    }
+
+    #[test]
+    fn test_add_clause_propagates_units() {
+        let mut solver = Solver::new();
+        let c1 = lits(&mut solver, &[-1, 2]);
+        let c2 = lits(&mut solver, &[1]);
+        assert!(solver.add_clause(c1));
+        assert!(solver.add_clause(c2));
+        assert_eq!(value(&mut solver, 2), LBool::True);
+
+        let c3 = lits(&mut solver, &[-2]);
+        assert!(!solver.add_clause(c3));
+        assert!(!solver.ok);
+    }
+}
@@ -0,0 +1,152 @@
+//! Target phases and rephasing (Biere & Fleury, 2020). The target phases
+//! are the assignment of the longest conflict-free trail since the last
+//! restart, which stable search tries to extend. Rephasing periodically
+//! resets the saved phases, to leave regions where they lead nowhere.
+
+use crate::models::{
+    mode::Mode,
+    rephase::{REPHASE_CYCLE_START, REPHASE_SCHEDULE, Rephase},
+    solver::Solver,
+};
+
+use super::branching::drand;
+
+impl Solver {
+    /// Returns true if decisions follow the target phases rather than the
+    /// saved phases.
+    pub fn use_target_phase(&self) -> bool {
+        self.target_phase > 1
+            || (self.target_phase == 1 && self.mode_switching && self.mode == Mode::Stable)
+    }
+
+    /// Records the phases of the first `consistent` literals of the trail,
+    /// which were propagated without conflict, if they extend the target or
+    /// best trail.
+    pub fn update_target_and_best(&mut self, consistent: usize) {
+        if consistent > self.target_assigned {
+            for &p in self.trail[..consistent].iter() {
+                self.target_polarity[p.var().into_usize()] = p.sign();
+            }
+            self.target_assigned = consistent;
+        }
+        if consistent > self.best_assigned {
+            for &p in self.trail[..consistent].iter() {
+                self.best_polarity[p.var().into_usize()] = p.sign();
+            }
+            self.best_assigned = consistent;
+        }
+    }
+
+    /// Resets the saved phases following the next entry of the schedule.
+    pub fn rephase(&mut self) {
+        let i = self.rephases as usize;
+        let kind = if i < REPHASE_SCHEDULE.len() {
+            REPHASE_SCHEDULE[i]
+        } else {
+            let cycle = REPHASE_SCHEDULE.len() - REPHASE_CYCLE_START;
+            REPHASE_SCHEDULE[REPHASE_CYCLE_START + (i - REPHASE_CYCLE_START) % cycle]
+        };
+        self.rephase_with(kind);
+        self.rephases += 1;
+        self.next_rephase = self.conflicts + self.rephase_inc * (self.rephases + 1);
+    }
+
+    pub fn rephase_with(&mut self, kind: Rephase) {
+        match kind {
+            Rephase::Original => self.polarity.clone_from(&self.original_polarity),
+            Rephase::Inverted => {
+                for (p, &o) in self.polarity.iter_mut().zip(self.original_polarity.iter()) {
+                    *p = !o;
+                }
+            }
+            Rephase::Best => self.polarity.clone_from(&self.best_polarity),
+            Rephase::Random => {
+                for v in 0..self.polarity.len() {
+                    self.polarity[v] = drand(&mut self.random_seed) < 0.5;
+                }
+            }
+            Rephase::Walk => self.walk(),
+        }
+        self.target_polarity.clone_from(&self.polarity);
+        self.target_assigned = 0;
+        self.best_assigned = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{clause::CRef, lbool::LBool},
+        test_utils::{lit, pigeonhole, slices, solver_with_clauses, solver_with_vars},
+    };
+
+    #[test]
+    fn test_target_and_best_follow_longest_trail() {
+        let mut solver = solver_with_vars(3);
+        for d in [1, -2, 3] {
+            solver.new_decision_level();
+            solver.unchecked_enqueue(lit(d), CRef::UNDEF);
+        }
+        solver.update_target_and_best(2);
+        assert_eq!(solver.target_polarity, vec![false, true, true]);
+        assert_eq!(solver.best_polarity, vec![false, true, true]);
+
+        // A shorter trail after a restart only replaces the target.
+        solver.cancel_until(0);
+        solver.target_assigned = 0;
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(2), CRef::UNDEF);
+        solver.update_target_and_best(1);
+        assert_eq!(solver.target_polarity, vec![false, false, true]);
+        assert_eq!(solver.best_polarity, vec![false, true, true]);
+        assert_eq!((solver.target_assigned, solver.best_assigned), (1, 2));
+    }
+
+    #[test]
+    fn test_rephase_schedule() {
+        let mut solver = solver_with_vars(2);
+        solver.best_polarity = vec![false, true];
+
+        solver.rephase();
+        assert_eq!(solver.polarity, vec![true, true]);
+        solver.rephase();
+        assert_eq!(solver.polarity, vec![false, false]);
+        solver.rephase();
+        assert_eq!(solver.polarity, vec![false, true]);
+        assert_eq!(solver.target_polarity, vec![false, true]);
+        assert_eq!(solver.best_assigned, 0);
+        for _ in 0..5 {
+            solver.rephase();
+        }
+        // Back to the start of the cycle:
+        solver.best_polarity = vec![true, false];
+        solver.rephase();
+        assert_eq!(solver.polarity, vec![true, false]);
+        assert_eq!(solver.rephases, 9);
+        assert_eq!(solver.next_rephase, 10000);
+    }
+
+    #[test]
+    fn test_decisions_follow_target_phase() {
+        let mut solver = solver_with_vars(1);
+        solver.target_phase = 2;
+        solver.target_polarity[0] = false;
+        assert_eq!(solver.pick_branch_lit(), lit(1));
+
+        solver.rebuild_order_heap();
+        solver.target_phase = 0;
+        assert_eq!(solver.pick_branch_lit(), lit(-1));
+    }
+
+    #[test]
+    fn test_solve_with_rephasing() {
+        let (num_vars, clauses) = pigeonhole(6, 5);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+        solver.rephasing = true;
+        solver.rephase_inc = 10;
+        solver.target_phase = 2;
+
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.rephases > 1);
+    }
+}
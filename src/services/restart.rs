@@ -0,0 +1,70 @@
+//! Glucose restarts (Audemard & Simon, 2012), with moving averages instead of
+//! bounded queues: restart while the recent learnt clauses are worse than
+//! usual, unless the trail suggests that a model may be close.
+
+use crate::models::solver::Solver;
+
+impl Solver {
+    /// Records the trail size at a conflict. Returns true if a pending
+    /// restart should be blocked, because the trail is much larger than on
+    /// average.
+    pub fn block_restart(&mut self, conflict_c: i32) -> bool {
+        let trail = self.trail.len() as f64;
+        let block = self.conflicts > self.restart_block_start
+            && conflict_c >= self.restart_min_conflicts
+            && trail > self.restart_block * self.trail_ema.value();
+        self.trail_ema.update(trail);
+        if block {
+            self.blocked_restarts += 1;
+        }
+        block
+    }
+
+    pub fn update_lbd_averages(&mut self, lbd: u32) {
+        self.lbd_ema_fast.update(lbd as f64);
+        self.lbd_ema_slow.update(lbd as f64);
+    }
+
+    /// Returns true if the recent learnt clauses have a higher LBD than the
+    /// learnt clauses overall, by at least the restart margin.
+    pub fn glucose_restart(&self, conflict_c: i32) -> bool {
+        conflict_c >= self.restart_min_conflicts
+            && self.lbd_ema_fast.value() > self.restart_margin * self.lbd_ema_slow.value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{clause::CRef, lit::Lit, var::Var};
+
+    use super::*;
+
+    #[test]
+    fn test_restart_on_rising_lbd() {
+        let mut solver = Solver::new();
+        for _ in 0..1000 {
+            solver.update_lbd_averages(4);
+        }
+        assert!(!solver.glucose_restart(100));
+        for _ in 0..50 {
+            solver.update_lbd_averages(10);
+        }
+        assert!(solver.glucose_restart(100));
+        assert!(!solver.glucose_restart(10));
+    }
+
+    #[test]
+    fn test_block_restart_on_large_trail() {
+        let mut solver = Solver::new();
+        solver.new_var(true, true);
+        solver.conflicts = solver.restart_block_start + 1;
+        for _ in 0..100 {
+            assert!(!solver.block_restart(100));
+        }
+        // The trail is empty: any assignment is above the average.
+        solver.unchecked_enqueue(Lit::new(Var::from(0), false), CRef::UNDEF);
+        assert!(!solver.block_restart(10));
+        assert!(solver.block_restart(100));
+        assert_eq!(solver.blocked_restarts, 1);
+    }
+}
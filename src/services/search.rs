@@ -0,0 +1,512 @@
+use crate::models::{clause::CRef, lbool::LBool, lit::Lit, restart::Restart, solver::Solver};
+
+#[cfg(feature = "lbd_based_clause_deletion")]
+use super::clause_deletion::TIER2_REDUCE_INTERVAL;
+
+/// Finite subsequences of the Luby-sequence:
+///
+/// 0: 1
+/// 1: 1 1 2
+/// 2: 1 1 2 1 1 2 4
+/// 3: 1 1 2 1 1 2 1 1 2 4 1 1 2 4 8
+/// ...
+pub fn luby(y: f64, mut x: i32) -> f64 {
+    // Find the finite subsequence that contains index 'x', and the
+    // size of that subsequence:
+    let mut size = 1;
+    let mut seq = 0;
+    while size < x + 1 {
+        seq += 1;
+        size = 2 * size + 1;
+    }
+
+    while size - 1 != x {
+        size = (size - 1) >> 1;
+        seq -= 1;
+        x %= size;
+    }
+
+    y.powi(seq)
+}
+
+impl Solver {
+    pub fn within_budget(&self) -> bool {
+        !self.asynch_interrupt
+            && (self.conflict_budget < 0 || self.conflicts < self.conflict_budget as u64)
+            && (self.propagation_budget < 0 || self.propagations < self.propagation_budget as u64)
+    }
+
+    /// Estimates the fraction of the search space that has been explored,
+    /// weighting the assignments of each level by its depth.
+    fn progress_estimate(&self) -> f64 {
+        let mut progress = 0.0;
+        let f = 1.0 / self.num_vars() as f64;
+
+        for i in 0..=self.decision_level() as usize {
+            let beg = if i == 0 {
+                0
+            } else {
+                self.trail_lim[i - 1] as usize
+            };
+            let end = if i == self.decision_level() as usize {
+                self.trail.len()
+            } else {
+                self.trail_lim[i] as usize
+            };
+            progress += f.powi(i as i32) * (end - beg) as f64;
+        }
+
+        progress / self.num_vars() as f64
+    }
+
+    /// Searches for a model until `nof_conflicts` conflicts have occurred
+    /// (a negative value means no limit), or until a Glucose restart, then
+    /// restarts.
+    ///
+    /// Returns `LBool::True` if a partial assignment that is consistent with
+    /// respect to the clause set is found, `LBool::False` if the clause set is
+    /// unsatisfiable and `LBool::Undefined` if the bound on the number of
+    /// conflicts is reached.
+    fn search(&mut self, nof_conflicts: i32) -> LBool {
+        assert!(self.ok);
+        let mut conflict_c = 0;
+        let mut learnt_clause: Vec<Lit> = vec![];
+        let glucose = self.restart_policy() == Restart::Glucose;
+        self.starts += 1;
+
+        loop {
+            let confl = self.propagate();
+            if confl != CRef::UNDEF {
+                // CONFLICT
+                self.conflicts += 1;
+                conflict_c += 1;
+                self.heuristic.on_conflict();
+                if glucose && self.block_restart(conflict_c) {
+                    conflict_c = 0;
+                }
+                let (conflict_level, only_one) = self.find_conflict_level(confl);
+                if conflict_level == 0 {
+                    return LBool::False;
+                }
+                // The levels below the conflict level are free of conflicts:
+                self.update_target_and_best(self.trail_lim[conflict_level as usize - 1] as usize);
+                if only_one {
+                    // Missed implication: it becomes unit at the level below.
+                    self.cancel_until(conflict_level - 1);
+                    continue;
+                }
+
+                learnt_clause.clear();
+                let backtrack_level = self.analyze(confl, &mut learnt_clause);
+                if (self.conf_to_chrono < 0 || self.conflicts > self.conf_to_chrono as u64)
+                    && self.chrono > -1
+                    && self.decision_level() - backtrack_level >= self.chrono
+                {
+                    self.chrono_backtrack += 1;
+                    self.cancel_until(conflict_level - 1);
+                } else {
+                    self.non_chrono_backtrack += 1;
+                    self.cancel_until(backtrack_level);
+                }
+
+                if learnt_clause.len() == 1 {
+                    self.unchecked_enqueue_at(learnt_clause[0], 0, CRef::UNDEF);
+                    if glucose {
+                        self.update_lbd_averages(1);
+                    }
+                } else {
+                    let cr = self.ca.alloc(&learnt_clause, true);
+                    self.add_learnt(cr);
+                    self.attach_clause(cr);
+                    if glucose {
+                        // (add_learnt already computed the LBD)
+                        #[cfg(feature = "lbd_based_clause_deletion")]
+                        let lbd = self.ca[cr].lbd();
+                        #[cfg(not(feature = "lbd_based_clause_deletion"))]
+                        let lbd = self.compute_lbd(cr);
+                        self.update_lbd_averages(lbd);
+                    }
+                    self.unchecked_enqueue_at(learnt_clause[0], backtrack_level, cr);
+                }
+                self.cla_decay_activity();
+
+                self.learntsize_adjust_cnt -= 1;
+                if self.learntsize_adjust_cnt == 0 {
+                    self.learntsize_adjust_confl *= self.learntsize_adjust_inc;
+                    self.learntsize_adjust_cnt = self.learntsize_adjust_confl as i32;
+                    self.max_learnts *= self.learntsize_inc;
+
+                    if self.verbosity >= 1 {
+                        println!(
+                            "c | {:9} | {:7} {:8} {:8} | {:8} {:8} {:6.0} | {:6.3} % |",
+                            self.conflicts,
+                            self.dec_vars as i64
+                                - self
+                                    .trail_lim
+                                    .first()
+                                    .map_or(self.trail.len() as i64, |&l| l as i64),
+                            self.clauses.len(),
+                            self.clauses_literals,
+                            self.max_learnts as i64,
+                            self.num_learnts(),
+                            self.learnts_literals as f64 / self.num_learnts() as f64,
+                            self.progress_estimate() * 100.0
+                        );
+                    }
+                }
+            } else {
+                // NO CONFLICT
+                if (nof_conflicts >= 0 && conflict_c >= nof_conflicts)
+                    || (glucose && self.glucose_restart(conflict_c))
+                    || self.mode_switch_due()
+                    || !self.within_budget()
+                {
+                    // Reached bound on number of conflicts:
+                    self.progress_estimate = self.progress_estimate();
+                    self.update_target_and_best(self.trail.len());
+                    self.target_assigned = 0;
+                    self.cancel_until(0);
+                    return LBool::Undefined;
+                }
+
+                // Simplify the set of problem clauses:
+                if self.decision_level() == 0 && !self.simplify() {
+                    return LBool::False;
+                }
+
+                #[cfg(feature = "lbd_based_clause_deletion")]
+                if self.conflicts >= self.next_tier2_reduce {
+                    self.next_tier2_reduce = self.conflicts + TIER2_REDUCE_INTERVAL;
+                    self.reduce_db_tier2();
+                }
+                if self.decision_level() == 0
+                    && self.rephasing
+                    && self.conflicts >= self.next_rephase
+                {
+                    self.rephase();
+                }
+                if self.learnts_local.len() as f64 - self.num_assigns() as f64 >= self.max_learnts {
+                    // Reduce the set of local learnt clauses:
+                    self.reduce_db();
+                }
+
+                let mut next = Lit::UNDEF;
+                while (self.decision_level() as usize) < self.assumptions.len() {
+                    // Perform user provided assumption:
+                    let p = self.assumptions[self.decision_level() as usize];
+                    if self.lit_value(&p) == LBool::True {
+                        // Dummy decision level:
+                        self.new_decision_level();
+                    } else if self.lit_value(&p) == LBool::False {
+                        let mut conflict = std::mem::take(&mut self.conflict);
+                        self.analyze_final(!p, &mut conflict);
+                        self.conflict = conflict;
+                        return LBool::False;
+                    } else {
+                        next = p;
+                        break;
+                    }
+                }
+
+                if next == Lit::UNDEF {
+                    // New variable decision:
+                    self.decisions += 1;
+                    next = self.pick_branch_lit();
+                    if next == Lit::UNDEF {
+                        // Model found:
+                        return LBool::True;
+                    }
+                }
+
+                // Increase decision level and enqueue 'next'
+                self.new_decision_level();
+                self.unchecked_enqueue(next, CRef::UNDEF);
+            }
+        }
+    }
+
+    /// Searches for a model of the clause set, restarting on the Luby or
+    /// geometric schedule. On `LBool::True` the satisfying assignment is
+    /// stored in `model`, extended to the clauses removed by preprocessing.
+    pub fn solve(&mut self) -> LBool {
+        self.solve_with(&[])
+    }
+
+    /// Like `solve`, but under the given assumptions, which are decided
+    /// before any other variable. If the clause set is unsatisfiable under
+    /// the assumptions, `conflict` holds the negation of the assumptions
+    /// responsible, or is empty if it is unsatisfiable regardless. The solver
+    /// can be extended and solved again afterwards.
+    pub fn solve_with(&mut self, assumps: &[Lit]) -> LBool {
+        self.assumptions.clear();
+        self.assumptions.extend_from_slice(assumps);
+        self.model.clear();
+        self.conflict.clear();
+        if !self.ok {
+            return LBool::False;
+        }
+
+        self.solves += 1;
+
+        self.max_learnts = self.clauses.len() as f64 * self.learntsize_factor;
+        self.learntsize_adjust_confl = self.learntsize_adjust_start_confl as f64;
+        self.learntsize_adjust_cnt = self.learntsize_adjust_confl as i32;
+
+        if self.verbosity >= 1 {
+            println!(
+                "c ============================[ Search Statistics ]=============================="
+            );
+            println!(
+                "c | Conflicts |          ORIGINAL         |          LEARNT          | Progress |"
+            );
+            println!(
+                "c |           |    Vars  Clauses Literals |    Limit  Clauses Lit/Cl |          |"
+            );
+            println!(
+                "c ==============================================================================="
+            );
+        }
+
+        let mut status = LBool::Undefined;
+
+        if self.mode_switching {
+            self.init_mode_switching();
+        }
+        if self.rephasing && self.next_rephase == 0 {
+            self.next_rephase = self.conflicts + self.rephase_inc;
+        }
+        if self.lcm && self.next_lcm == 0 {
+            self.next_lcm = self.conflicts + self.lcm_interval;
+        }
+        if self.probing && self.next_probe == 0 && !self.probe() {
+            status = LBool::False;
+        }
+
+        // Search:
+        let mut curr_restarts = 0;
+        while status == LBool::Undefined {
+            let restart_first = self.restart_first() as f64;
+            let nof_conflicts = match self.restart_policy() {
+                Restart::Luby => luby(self.restart_inc, curr_restarts) * restart_first,
+                Restart::Geometric => self.restart_inc.powi(curr_restarts) * restart_first,
+                Restart::Glucose => -1.0,
+            };
+            status = self.search(nof_conflicts as i32);
+            if !self.within_budget() {
+                break;
+            }
+            if status == LBool::Undefined
+                && self.lcm
+                && self.conflicts >= self.next_lcm
+                && !self.minimize_learnts()
+            {
+                status = LBool::False;
+            }
+            if status == LBool::Undefined
+                && self.probing
+                && self.conflicts >= self.next_probe
+                && !self.probe()
+            {
+                status = LBool::False;
+            }
+            if self.mode_switch_due() {
+                self.switch_mode();
+            } else {
+                curr_restarts += 1;
+            }
+        }
+
+        if self.mode_switching {
+            self.account_mode();
+        }
+
+        if self.verbosity >= 1 {
+            println!(
+                "c ==============================================================================="
+            );
+        }
+
+        if status == LBool::True {
+            // Copy model:
+            self.model = self.assigns.clone();
+            self.reconstruction.extend(&mut self.model);
+        } else if status == LBool::False && self.conflict.is_empty() {
+            self.ok = false;
+        }
+
+        self.cancel_until(0);
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{
+        lit, pigeonhole, satisfies, slices, solver_with_clauses, solver_with_vars,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_luby_sequence() {
+        let seq: Vec<f64> = (0..15).map(|i| luby(2.0, i)).collect();
+        assert_eq!(
+            seq,
+            vec![
+                1.0, 1.0, 2.0, 1.0, 1.0, 2.0, 4.0, 1.0, 1.0, 2.0, 1.0, 1.0, 2.0, 4.0, 8.0
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solve_satisfiable() {
+        let (num_vars, clauses) = pigeonhole(5, 5);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+
+        assert_eq!(solver.solve(), LBool::True);
+        assert_eq!(solver.model.len(), num_vars);
+        assert!(satisfies(&solver.model, &clauses));
+        assert_eq!(solver.decision_level(), 0);
+    }
+
+    #[test]
+    fn test_solve_unsatisfiable() {
+        let (num_vars, clauses) = pigeonhole(5, 4);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.model.is_empty());
+        assert!(!solver.ok);
+        assert!(solver.conflicts > 0);
+    }
+
+    #[test]
+    fn test_solve_with_geometric_restarts() {
+        let (num_vars, clauses) = pigeonhole(6, 5);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+        solver.restart = Restart::Geometric;
+        solver.restart_first = 10;
+        solver.restart_inc = 1.5;
+
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.starts > 1);
+    }
+
+    #[test]
+    fn test_solve_with_chronological_backtracking() {
+        let (num_vars, clauses) = pigeonhole(6, 5);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+        solver.chrono = 0;
+        solver.conf_to_chrono = -1;
+
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.chrono_backtrack > 0);
+        assert_eq!(solver.non_chrono_backtrack, 0);
+    }
+
+    #[test]
+    fn test_solve_with_glucose_restarts() {
+        let (num_vars, clauses) = pigeonhole(7, 6);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+        solver.restart = Restart::Glucose;
+
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.lbd_ema_slow.value() > 1.0);
+    }
+
+    #[test]
+    fn test_glucose_restarts_happen() {
+        // Large enough to restart with every branching heuristic:
+        let (num_vars, clauses) = pigeonhole(8, 7);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+        solver.restart = Restart::Glucose;
+
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.starts > 1);
+    }
+
+    #[test]
+    fn test_solve_stops_at_conflict_budget() {
+        let (num_vars, clauses) = pigeonhole(7, 6);
+        let mut solver = solver_with_clauses(num_vars, &slices(&clauses));
+        solver.conflict_budget = 10;
+
+        assert_eq!(solver.solve(), LBool::Undefined);
+        assert!(solver.ok);
+        assert_eq!(solver.decision_level(), 0);
+    }
+
+    #[test]
+    fn test_solve_trivially_unsatisfiable() {
+        let mut solver = solver_with_clauses(1, &[&[1]]);
+        assert!(!solver.add_clause(vec![lit(-1)]));
+        assert_eq!(solver.solve(), LBool::False);
+    }
+
+    #[test]
+    fn test_solve_with_satisfiable_assumptions() {
+        let clauses = vec![vec![1, 2], vec![-1, 3], vec![-2, -3]];
+        let mut solver = solver_with_clauses(3, &slices(&clauses));
+
+        assert_eq!(solver.solve_with(&[lit(-3)]), LBool::True);
+        assert!(satisfies(&solver.model, &clauses));
+        assert_eq!(lit(-3).value(&solver.model), LBool::True);
+        assert!(solver.conflict.is_empty());
+    }
+
+    #[test]
+    fn test_solve_with_final_conflict() {
+        // x1 and x2 together imply x4 and -x4. x3 is irrelevant.
+        let clauses = vec![vec![-1, -2, 4], vec![-1, -2, -4], vec![3, 4]];
+        let mut solver = solver_with_clauses(4, &slices(&clauses));
+
+        assert_eq!(solver.solve_with(&[lit(1), lit(3), lit(2)]), LBool::False);
+        let mut conflict = solver.conflict.clone();
+        conflict.sort();
+        assert_eq!(conflict, vec![lit(-1), lit(-2)]);
+        assert!(solver.ok);
+
+        // The solver can be reused, with other assumptions or none.
+        assert_eq!(solver.solve_with(&[lit(1), lit(-2)]), LBool::True);
+        assert!(satisfies(&solver.model, &clauses));
+        assert_eq!(solver.solve(), LBool::True);
+        assert!(solver.conflict.is_empty());
+    }
+
+    #[test]
+    fn test_solve_with_contradicting_assumptions() {
+        let mut solver = solver_with_clauses(2, &[&[1, 2]]);
+
+        assert_eq!(solver.solve_with(&[lit(1), lit(-1)]), LBool::False);
+        let mut conflict = solver.conflict.clone();
+        conflict.sort();
+        assert_eq!(conflict, vec![lit(1), lit(-1)]);
+        assert!(solver.ok);
+    }
+
+    #[test]
+    fn test_solve_with_incremental_clauses() {
+        let (num_vars, clauses) = pigeonhole(5, 4);
+        let mut solver = solver_with_vars(num_vars);
+        solver.chrono = 0;
+        solver.conf_to_chrono = -1;
+        // Activate each clause with a fresh selector literal.
+        let mut selectors = vec![];
+        for c in clauses.iter() {
+            let s = solver.new_var(true, true);
+            let mut ps: Vec<Lit> = c.iter().map(|&x| lit(x)).collect();
+            ps.push(Lit::new(s, false));
+            assert!(solver.add_clause(ps));
+            selectors.push(Lit::new(s, true));
+        }
+
+        assert_eq!(solver.solve_with(&selectors[1..]), LBool::True);
+        assert_eq!(solver.solve_with(&selectors), LBool::False);
+        assert!(!solver.conflict.is_empty());
+        assert!(solver.conflict.iter().all(|&p| selectors.contains(&!p)));
+        assert_eq!(
+            solver.solve_with(&selectors[..selectors.len() - 1]),
+            LBool::True
+        );
+    }
+}
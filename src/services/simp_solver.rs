@@ -0,0 +1,166 @@
+use std::collections::VecDeque;
+
+use crate::models::{
+    clause::CRef, heap::Heap, lbool::LBool, lit::Lit, occ_lists::OccLists, simp_solver::SimpSolver,
+    solver::Solver, var::Var,
+};
+
+impl Default for SimpSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimpSolver {
+    pub fn new() -> Self {
+        Self::with_solver(Solver::new())
+    }
+
+    /// Preprocesses for the given solver, which must not have any variables
+    /// yet.
+    pub fn with_solver(mut solver: Solver) -> Self {
+        assert!(solver.num_vars() == 0);
+        solver.ca.extra_clause_field = true;
+        SimpSolver {
+            solver,
+            grow: 0,
+            clause_lim: 20,
+            subsumption_lim: 1000,
+            use_elim: true,
+            use_equiv: true,
+            merges: 0,
+            eliminated_vars: 0,
+            substituted_vars: 0,
+            subsumed_clauses: 0,
+            deleted_literals: 0,
+            use_simplification: true,
+            frozen: vec![],
+            eliminated: vec![],
+            occurs: OccLists::new(),
+            n_occ: vec![],
+            elim_heap: Heap::new(),
+            elim_cost: vec![],
+            resolvent: vec![],
+            subsumption_queue: VecDeque::new(),
+            touched: vec![],
+            n_touched: 0,
+            bwdsub_assigns: 0,
+            bwdsub_tmpunit: CRef::UNDEF,
+        }
+    }
+
+    pub fn new_var(&mut self, sign: bool, dvar: bool) -> Var {
+        let v = self.solver.new_var(sign, dvar);
+        self.frozen.push(false);
+        self.eliminated.push(false);
+        self.occurs.init(v);
+        self.n_occ.push(0);
+        self.n_occ.push(0);
+        self.elim_cost.push(0.0);
+        self.touched.push(false);
+        v
+    }
+
+    /// Adds a clause, which must not contain eliminated variables.
+    pub fn add_clause(&mut self, ps: Vec<Lit>) -> bool {
+        assert!(ps.iter().all(|p| !self.is_eliminated(p.var())));
+        self.solver.add_clause(ps)
+    }
+
+    /// Frozen variables are kept when eliminating variables, so they can be
+    /// used in later clauses and assumptions.
+    pub fn set_frozen(&mut self, v: Var, b: bool) {
+        self.frozen[v.into_usize()] = b;
+    }
+
+    pub fn is_eliminated(&self, v: Var) -> bool {
+        self.eliminated[v.into_usize()]
+    }
+
+    pub fn solve(&mut self) -> LBool {
+        self.solve_limited(&[], true, false)
+    }
+
+    pub fn solve_with(&mut self, assumps: &[Lit]) -> LBool {
+        self.solve_limited(assumps, true, false)
+    }
+
+    /// Solves under the assumptions, eliminating variables first if
+    /// `do_simp`. With `turn_off_simp`, the preprocessing is turned off for
+    /// good afterwards.
+    pub fn solve_limited(&mut self, assumps: &[Lit], do_simp: bool, turn_off_simp: bool) -> LBool {
+        let do_simp = do_simp && self.use_simplification;
+        let mut extra_frozen = vec![];
+        let mut result = LBool::True;
+
+        if do_simp {
+            // Assumptions must be temporarily frozen to run variable elimination:
+            for p in assumps.iter() {
+                let v = p.var();
+                assert!(!self.is_eliminated(v));
+                if !self.frozen[v.into_usize()] {
+                    self.set_frozen(v, true);
+                    extra_frozen.push(v);
+                }
+            }
+            if !self.eliminate(turn_off_simp) {
+                result = LBool::False;
+            }
+        }
+
+        if result == LBool::True {
+            result = self.solver.solve_with(assumps);
+        }
+
+        // Unfreeze the assumptions that were frozen:
+        for v in extra_frozen {
+            self.set_frozen(v, false);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{clause, lit, satisfies, simp_with_clauses};
+
+    #[test]
+    fn test_solve_extends_model() {
+        let clauses: &[&[i32]] = &[&[1, 2], &[-1, 3], &[-2, 3], &[-3, 4, 5], &[-4, -5]];
+        let mut simp = simp_with_clauses(5, clauses);
+
+        assert_eq!(simp.solve(), LBool::True);
+        assert!(simp.eliminated_vars > 0);
+        assert!(satisfies(&simp.model, clauses));
+    }
+
+    #[test]
+    fn test_solve_unsatisfiable() {
+        let clauses: &[&[i32]] = &[&[1, 2], &[-1, 2], &[1, -2], &[-1, -2, 3], &[-1, -2, -3]];
+        let mut simp = simp_with_clauses(3, clauses);
+
+        assert_eq!(simp.solve(), LBool::False);
+        assert!(!simp.ok);
+    }
+
+    #[test]
+    fn test_assumptions_and_frozen_vars_are_not_eliminated() {
+        let clauses: &[&[i32]] = &[&[1, 2], &[-2, 3]];
+        let mut simp = simp_with_clauses(3, clauses);
+        simp.set_frozen(lit(1).var(), true);
+
+        assert_eq!(simp.solve_with(&[lit(-3)]), LBool::True);
+        assert!(simp.is_eliminated(lit(2).var()));
+        assert!(!simp.is_eliminated(lit(1).var()));
+        assert!(!simp.is_eliminated(lit(3).var()));
+        assert!(!simp.frozen[2]);
+        assert_eq!(lit(-3).value(&simp.model), LBool::True);
+        assert!(satisfies(&simp.model, clauses));
+
+        // Frozen variables may be used in later clauses:
+        assert!(simp.add_clause(clause(&[-1])));
+        assert_eq!(simp.solve(), LBool::True);
+        assert!(satisfies(&simp.model, &[&[-1], clauses[0], clauses[1]]));
+    }
+}
@@ -1,28 +1,51 @@
 use rand::{Rng, SeedableRng};
 
 use crate::models::{
-    clause::{Clause, UNDEF_CLAUSE},
+    branching_heuristic::{Branching, BranchingHeuristic},
+    clause::{CRef, Clause, LOCAL, TIER2},
+    clause_allocator::ClauseAllocator,
+    ema::Ema,
+    heap::Heap,
     lbool::LBool,
     lit::Lit,
+    mode::{Mode, ModeStats},
+    occ_lists::OccLists,
+    reconstruction_stack::ReconstructionStack,
+    restart::Restart,
     solver::Solver,
     var::Var,
     var_data::VarData,
+    walker::WalkScoring,
+    watcher::Watcher,
 };
 
-impl<'a> Solver<'a> {
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Solver {
     pub fn new() -> Self {
         Self {
             model: vec![],
             conflict: vec![],
             verbosity: 0,
-            step_size: 0.40,
-            step_size_dec: 0.000001,
-            min_step_size: 0.06,
+            heuristic: Branching::default().heuristic(),
+            other_heuristic: Branching::default().heuristic(),
+            clause_decay: 0.999,
+            core_lbd_cut: 2,
+            tier2_lbd_cut: 6,
             random_var_freq: 0.02,
-            random_seed: 0.0,
-            luby_restart: true,
+            random_seed: 91648253.0,
+            restart: Restart::default(),
             ccmin_mode: 2,
             phase_saving: 2,
+            target_phase: 1,
+            rephasing: false,
+            walk_scoring: WalkScoring::default(),
+            walk_flips: 100000,
+            rephase_inc: 1000,
             rnd_pol: false,
             rnd_init_act: false,
             garbage_frac: 0.20,
@@ -30,8 +53,25 @@ impl<'a> Solver<'a> {
             restart_inc: 2.0,
             learntsize_factor: 1.0 / 3.0,
             learntsize_inc: 1.1,
+            restart_margin: 1.25,
+            restart_block: 1.4,
+            restart_block_start: 10000,
+            restart_min_conflicts: 50,
+            mode_switching: false,
+            mode_switch_first: 1000,
+            mode_switch_inc: 2.0,
+            stable_restart_first: 1000,
+            lcm: true,
+            lcm_interval: 1000,
+            lcm_inc: 1000,
+            probing: false,
+            probe_interval: 10000,
+            probe_budget: 100000,
+            probe_hbr_limit: 1000,
             learntsize_adjust_start_confl: 100,
             learntsize_adjust_inc: 1.5,
+            chrono: 100,
+            conf_to_chrono: 4000,
             solves: 0,
             starts: 0,
             decisions: 0,
@@ -43,35 +83,77 @@ impl<'a> Solver<'a> {
             learnts_literals: 0,
             max_literals: 0,
             tot_literals: 0,
+            bin_minimized_lits: 0,
+            blocked_restarts: 0,
+            mode_switches: 0,
+            rephases: 0,
+            walks: 0,
+            walk_flips_total: 0,
+            walk_best_unsat: 0,
+            lcm_runs: 0,
+            lcm_tested_lits: 0,
+            lcm_removed_lits: 0,
+            probe_runs: 0,
+            probed_lits: 0,
+            failed_literals: 0,
+            probe_units: 0,
+            hbr_resolvents: 0,
+            focused_stats: ModeStats::default(),
+            stable_stats: ModeStats::default(),
+            chrono_backtrack: 0,
+            non_chrono_backtrack: 0,
             lbd_calls: 0,
             lbd_seen: vec![],
-            picked: vec![],
-            conflicted: vec![],
-            almost_conflicted: vec![],
-            canceled: vec![],
             total_actual_rewards: vec![],
             total_actual_count: vec![],
             ok: true,
+            clauses: vec![],
+            learnts_core: vec![],
+            learnts_tier2: vec![],
+            learnts_local: vec![],
             cla_inc: 1.0,
             activity: vec![],
-            var_inc: 1.0,
+            watches: OccLists::new(),
+            watches_bin: OccLists::new(),
             assigns: vec![],
             polarity: vec![],
+            original_polarity: vec![],
+            target_polarity: vec![],
+            best_polarity: vec![],
+            target_assigned: 0,
+            best_assigned: 0,
+            next_rephase: 0,
+            next_lcm: 0,
+            next_probe: 0,
+            probe_next_var: 0,
+            probe_hbr_end: 0,
             decision: vec![],
             trail: vec![],
             trail_lim: vec![],
             vardata: vec![],
             qhead: 0,
-            simpDB_assigns: -1,
-            simpDB_props: 0,
+            simp_db_assigns: -1,
+            simp_db_props: 0,
             assumptions: vec![],
+            order_heap: Heap::new(),
             progress_estimate: 0.0,
+            lbd_ema_fast: Ema::new(0.03),
+            lbd_ema_slow: Ema::new(1e-5),
+            trail_ema: Ema::new(1.0 / 5000.0),
+            mode: Mode::Focused,
+            mode_length: 0.0,
+            next_mode_switch: 0,
+            mode_start: ModeStats::default(),
+            other_activity: vec![],
+            reconstruction: ReconstructionStack::new(),
             remove_satisfied: true,
+            ca: ClauseAllocator::new(),
             seen: vec![],
             analyze_stack: vec![],
             analyze_toclear: vec![],
             add_tmp: vec![],
             max_learnts: 0.0,
+            next_tier2_reduce: 0,
             learntsize_adjust_confl: 0.0,
             learntsize_adjust_cnt: 0,
             conflict_budget: -1,
@@ -80,30 +162,51 @@ impl<'a> Solver<'a> {
         }
     }
 
-    fn decision_level(&self) -> i32 {
-        return self.trail_lim.len() as i32;
+    /// Uses the given branching heuristic. Must be called before any
+    /// variable is created.
+    pub fn with_branching(self, branching: Branching) -> Self {
+        self.with_heuristic(branching.heuristic())
     }
 
-    // TODO: Compare with MapleSAT's efficiency
-    fn lit_value(&self, p: &Lit) -> LBool {
-        let value = p.var().value(&self.assigns);
-        if value == LBool::Undefined {
-            LBool::Undefined
-        } else {
-            let value = value == LBool::True;
-            if !p.sign() {
-                LBool::from(!value)
-            } else {
-                LBool::from(value)
-            }
-        }
+    /// Uses a custom branching heuristic. Must be called before any variable
+    /// is created.
+    pub fn with_heuristic(mut self, heuristic: Box<dyn BranchingHeuristic>) -> Self {
+        assert!(self.num_vars() == 0);
+        self.heuristic = heuristic;
+        self
+    }
+
+    /// Selects the branching heuristic of the stable mode, used with mode
+    /// switching. Must be called before any variable is created.
+    pub fn with_stable_branching(self, branching: Branching) -> Self {
+        self.with_stable_heuristic(branching.heuristic())
+    }
+
+    /// Uses a custom branching heuristic in the stable mode. Must be called
+    /// before any variable is created.
+    pub fn with_stable_heuristic(mut self, heuristic: Box<dyn BranchingHeuristic>) -> Self {
+        assert!(self.num_vars() == 0 && self.mode == Mode::Focused);
+        self.other_heuristic = heuristic;
+        self
+    }
+
+    pub fn decision_level(&self) -> i32 {
+        self.trail_lim.len() as i32
+    }
+
+    pub fn new_decision_level(&mut self) {
+        self.trail_lim.push(self.trail.len() as i32);
+    }
+
+    pub fn lit_value(&self, p: &Lit) -> LBool {
+        p.value(&self.assigns)
     }
 
     pub fn num_vars(&self) -> usize {
         self.vardata.len()
     }
 
-    fn set_decision_var(&mut self, v: Var, b: bool) {
+    pub fn set_decision_var(&mut self, v: Var, b: bool) {
         let v_usize: usize = v.into();
         if b && !self.decision[v_usize] {
             self.dec_vars += 1;
@@ -112,80 +215,234 @@ impl<'a> Solver<'a> {
         }
 
         self.decision[v_usize] = b;
-        // TODO: self.insert_var_order(v);
+        self.insert_var_order(v_usize);
     }
 
-    fn new_var(&mut self, sign: bool, dvar: bool) -> Var {
+    pub fn new_var(&mut self, sign: bool, dvar: bool) -> Var {
         let new_var = Var::from(self.num_vars());
-        // TODO: Add watches
-        // watches  .init(mkLit(v, false));
-        // watches  .init(mkLit(v, true ));
+        self.watches.init(Lit::new(new_var, false));
+        self.watches.init(Lit::new(new_var, true));
+        self.watches_bin.init(Lit::new(new_var, false));
+        self.watches_bin.init(Lit::new(new_var, true));
         self.assigns.push(LBool::Undefined);
-        self.vardata.push(VarData {
-            reason: &UNDEF_CLAUSE,
-            level: 0,
-        });
+        self.vardata.push(VarData::new(CRef::UNDEF, 0));
         // Note: Behaves differently from MapleSAT
-        self.activity.push(if self.rnd_init_act {
+        let act = if self.rnd_init_act {
             let mut rng = rand::rngs::StdRng::seed_from_u64(self.random_seed as u64);
             rng.random_range(0.0..=1.0) * 0.00001
         } else {
             0.0
-        });
-        self.seen.push('\0');
+        };
+        self.activity.push(act);
+        self.other_activity.push(act);
+        self.seen.push(0);
         self.polarity.push(sign);
+        self.original_polarity.push(sign);
+        self.target_polarity.push(sign);
+        self.best_polarity.push(sign);
         self.decision.push(false);
         self.trail.reserve(1);
         self.lbd_seen.push(0);
-        self.picked.push(0);
-        self.conflicted.push(0);
-        #[cfg(feature = "almost_conflict")]
-        self.almost_conflicted.push(0);
-        #[cfg(feature = "anti_exploration")]
-        self.canceled.push(0);
-        #[cfg(feature = "bh_chb")]
-        self.last_conflict.push(0);
+        self.heuristic.new_var();
+        self.other_heuristic.new_var();
         self.total_actual_rewards.push(0.0);
         self.total_actual_count.push(0);
-        self.set_decision_var(new_var.clone(), dvar);
+        self.set_decision_var(new_var, dvar);
 
         new_var
     }
 
-    fn satisfied(&self, c: &Clause) -> bool {
+    pub fn satisfied(&self, c: &Clause) -> bool {
         c.iter().any(|lit| self.lit_value(lit) == LBool::True)
     }
 
-    // TODO: Finish
-    // fn unchecked_enqueue(&mut self, p: Lit, from: CRef) {
-    //     assert!(self.value(p) == LBool::Undef);
-    //     self.picked[p.var() as usize] = self.conflicts;
-
-    //     #[cfg(feature = "anti_exploration")]
-    //     {
-    //         let age = self.conflicts - self.canceled[p.var() as usize];
-    //         if age > 0 {
-    //             let decay = 0.95f64.powi(age as i32);
-    //             self.activity[p.var() as usize] *= decay;
-    //             if self.order_heap.in_heap(p.var() as usize) {
-    //                 self.order_heap.increase(p.var() as usize);
-    //             }
-    //         }
-    //     }
-
-    //     self.conflicted[p.var() as usize] = 0;
-
-    //     #[cfg(feature = "almost_conflict")]
-    //     {
-    //         self.almost_conflicted[p.var() as usize] = 0;
-    //     }
-
-    //     self.assigns[p.var() as usize] = LBool::from(!p.sign());
-    //     self.vardata[p.var() as usize] = VarData::new(from, self.decision_level());
-    //     self.trail.push(p);
-    // }
-
-    // TODO: Finish
+    pub fn reason(&self, v: Var) -> CRef {
+        self.vardata[v.into_usize()].reason
+    }
+
+    pub fn level(&self, v: Var) -> i32 {
+        self.vardata[v.into_usize()].level
+    }
+
+    pub fn unchecked_enqueue(&mut self, p: Lit, from: CRef) {
+        self.unchecked_enqueue_at(p, self.decision_level(), from);
+    }
+
+    /// Assigns `p` at the given level, which may be lower than the current
+    /// decision level when backtracking chronologically.
+    pub fn unchecked_enqueue_at(&mut self, p: Lit, level: i32, from: CRef) {
+        assert!(self.lit_value(&p) == LBool::Undefined);
+        let v = p.var().into_usize();
+        self.heuristic
+            .on_assign(v, self.conflicts, &mut self.activity, &mut self.order_heap);
+        self.assigns[v] = LBool::from(!p.sign());
+        self.vardata[v] = VarData::new(from, level);
+        self.trail.push(p);
+    }
+
+    /// Reverts to the state at the given level, keeping all assignments at
+    /// `level` but not beyond. Literals implied out of order at or below
+    /// `level` stay on the trail and are propagated again.
+    pub fn cancel_until(&mut self, level: i32) {
+        if self.decision_level() > level {
+            let lim = self.trail_lim[level as usize] as usize;
+            let last_lim = *self.trail_lim.last().unwrap() as usize;
+            self.add_tmp.clear();
+            for c in (lim..self.trail.len()).rev() {
+                let x = self.trail[c].var().into_usize();
+                if self.vardata[x].level <= level {
+                    self.add_tmp.push(self.trail[c]);
+                    continue;
+                }
+                self.heuristic.on_unassign(
+                    x,
+                    self.conflicts,
+                    &mut self.activity,
+                    &mut self.order_heap,
+                );
+                self.assigns[x] = LBool::Undefined;
+                if self.phase_saving > 1 || (self.phase_saving == 1 && c > last_lim) {
+                    self.polarity[x] = self.trail[c].sign();
+                }
+                self.insert_var_order(x);
+            }
+            self.qhead = lim as i32;
+            self.trail.truncate(lim);
+            self.trail_lim.truncate(level as usize);
+            for i in (0..self.add_tmp.len()).rev() {
+                self.trail.push(self.add_tmp[i]);
+            }
+            self.add_tmp.clear();
+        }
+    }
+
+    /// Watches the first two literals of the clause. Binary clauses go to
+    /// their own watch lists.
+    pub fn attach_clause(&mut self, cr: CRef) {
+        let c = &self.ca[cr];
+        assert!(c.size() > 1);
+        let (c0, c1) = (c[0], c[1]);
+        let ws = if c.size() == 2 {
+            &mut self.watches_bin
+        } else {
+            &mut self.watches
+        };
+        ws[!c0].push(Watcher::new(cr, c1));
+        ws[!c1].push(Watcher::new(cr, c0));
+        if c.learnt() {
+            self.learnts_literals += c.size() as u64;
+        } else {
+            self.clauses_literals += c.size() as u64;
+        }
+    }
+
+    /// Detaches the clause. Unless `strict`, its watch lists are only smudged
+    /// and get cleaned before the next propagation or garbage collection.
+    pub fn detach_clause(&mut self, cr: CRef, strict: bool) {
+        let c = &self.ca[cr];
+        assert!(c.size() > 1);
+        let (c0, c1) = (c[0], c[1]);
+        let ws = if c.size() == 2 {
+            &mut self.watches_bin
+        } else {
+            &mut self.watches
+        };
+        if strict {
+            ws[!c0].retain(|w| w.cref != cr);
+            ws[!c1].retain(|w| w.cref != cr);
+        } else {
+            ws.smudge(!c0);
+            ws.smudge(!c1);
+        }
+        if c.learnt() {
+            self.learnts_literals -= c.size() as u64;
+        } else {
+            self.clauses_literals -= c.size() as u64;
+        }
+    }
+
+    /// Detaches the clause and frees its memory.
+    pub fn remove_clause(&mut self, cr: CRef) {
+        self.detach_clause(cr, false);
+        // Don't leave pointers to free'd memory!
+        if self.locked(cr) {
+            let v = self.ca[cr][0].var();
+            self.vardata[v.into_usize()].reason = CRef::UNDEF;
+        }
+        self.ca[cr].set_mark(1);
+        self.ca.free(cr);
+    }
+
+    /// Returns true if the clause is the reason for the assignment of its first literal.
+    pub fn locked(&self, cr: CRef) -> bool {
+        let c0 = self.ca[cr][0];
+        self.lit_value(&c0) == LBool::True && self.reason(c0.var()) == cr
+    }
+
+    pub fn num_assigns(&self) -> usize {
+        self.trail.len()
+    }
+
+    fn remove_satisfied(&mut self, cs: &mut Vec<CRef>) {
+        cs.retain(|&cr| {
+            if self.satisfied(&self.ca[cr]) {
+                self.remove_clause(cr);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Removes the satisfied learnt clauses of every tier. The entries left
+    /// behind in the lists of lower tiers by promoted clauses are dropped.
+    fn remove_satisfied_learnts(&mut self) {
+        let mut learnts = std::mem::take(&mut self.learnts_core);
+        self.remove_satisfied(&mut learnts);
+        self.learnts_core = learnts;
+
+        let mut learnts = std::mem::take(&mut self.learnts_tier2);
+        learnts.retain(|&cr| self.ca[cr].mark() == TIER2);
+        self.remove_satisfied(&mut learnts);
+        self.learnts_tier2 = learnts;
+
+        let mut learnts = std::mem::take(&mut self.learnts_local);
+        learnts.retain(|&cr| self.ca[cr].mark() == LOCAL);
+        self.remove_satisfied(&mut learnts);
+        self.learnts_local = learnts;
+    }
+
+    /// Simplifies the clause database according to the current top-level
+    /// assignment. Currently, the only thing done here is the removal of
+    /// satisfied clauses, but more things can be put here.
+    pub fn simplify(&mut self) -> bool {
+        assert!(self.decision_level() == 0);
+
+        if !self.ok || self.propagate() != CRef::UNDEF {
+            self.ok = false;
+            return false;
+        }
+
+        if self.num_assigns() as i32 == self.simp_db_assigns || self.simp_db_props > 0 {
+            return true;
+        }
+
+        // Remove satisfied clauses:
+        self.remove_satisfied_learnts();
+        if self.remove_satisfied {
+            let mut clauses = std::mem::take(&mut self.clauses);
+            self.remove_satisfied(&mut clauses);
+            self.clauses = clauses;
+        }
+        self.check_garbage();
+        self.rebuild_order_heap();
+
+        self.simp_db_assigns = self.num_assigns() as i32;
+        self.simp_db_props = (self.clauses_literals + self.learnts_literals) as i64;
+
+        true
+    }
+
     pub fn add_clause(&mut self, mut ps: Vec<Lit>) -> bool {
         assert!(self.decision_level() == 0);
         if !self.ok {
@@ -193,8 +450,8 @@ impl<'a> Solver<'a> {
         }
 
         // Check if clause is satisfied and remove false/duplicate literals:
-        // TODO: ps.sort();
-        let mut p = Lit::default();
+        ps.sort();
+        let mut p = Lit::UNDEF;
         let mut j = 0;
         for i in 0..ps.len() {
             if ps[i].value(&self.assigns) == LBool::True || ps[i] == !p {
@@ -211,13 +468,13 @@ impl<'a> Solver<'a> {
             self.ok = false;
             return false;
         } else if ps.len() == 1 {
-            // TODO: self.unchecked_enqueue(ps[0]);
-            // TODO: self.ok = self.propagate() == CRef::Undef;
+            self.unchecked_enqueue(ps[0], CRef::UNDEF);
+            self.ok = self.propagate() == CRef::UNDEF;
             return self.ok;
         } else {
-            // TODO: let cr = self.ca.alloc(&ps, false);
-            // TODO: self.clauses.push(cr);
-            // TODO: self.attach_clause(cr);
+            let cr = self.ca.alloc(&ps, false);
+            self.clauses.push(cr);
+            self.attach_clause(cr);
         }
 
         true
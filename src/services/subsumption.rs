@@ -0,0 +1,229 @@
+//! Backward subsumption and self-subsuming resolution (Eén and Biere, SAT
+//! 2005). Each queued clause is checked against the original clauses of its
+//! least occurring variable: the clauses it subsumes are removed, and those
+//! it can resolve with into a subset of themselves lose the resolved literal.
+//! The 64-bit clause abstractions rule out most candidates cheaply.
+
+use crate::models::{clause::CRef, lbool::LBool, lit::Lit, simp_solver::SimpSolver};
+
+impl SimpSolver {
+    /// Queues the clauses of the variables touched since the last call.
+    pub fn gather_touched(&mut self) {
+        if self.n_touched == 0 {
+            return;
+        }
+
+        // Clauses already in the queue are temporarily marked 2 to not add
+        // them twice:
+        for &cr in self.subsumption_queue.iter() {
+            if self.solver.ca[cr].mark() == 0 {
+                self.solver.ca[cr].set_mark(2);
+            }
+        }
+        for v in 0..self.solver.num_vars() {
+            if !self.touched[v] {
+                continue;
+            }
+            let v = v.into();
+            let ca = &self.solver.ca;
+            self.occurs.clean(v, |&cr| ca[cr].mark() == 1);
+            for &cr in self.occurs[v].iter() {
+                if self.solver.ca[cr].mark() == 0 {
+                    self.subsumption_queue.push_back(cr);
+                    self.solver.ca[cr].set_mark(2);
+                }
+            }
+            self.touched[v.into_usize()] = false;
+        }
+        for &cr in self.subsumption_queue.iter() {
+            if self.solver.ca[cr].mark() == 2 {
+                self.solver.ca[cr].set_mark(0);
+            }
+        }
+        self.n_touched = 0;
+    }
+
+    /// Checks the queued clauses and the new top-level assignments for
+    /// backward subsumption. Returns false if a strengthened clause made the
+    /// clause set unsatisfiable.
+    pub fn backward_subsumption_check(&mut self) -> bool {
+        assert!(self.solver.decision_level() == 0);
+
+        while !self.subsumption_queue.is_empty() || self.bwdsub_assigns < self.solver.trail.len() {
+            if self.solver.asynch_interrupt {
+                self.subsumption_queue.clear();
+                self.bwdsub_assigns = self.solver.trail.len();
+                break;
+            }
+
+            // Check top-level assignments by placing a unit clause in the queue:
+            if self.subsumption_queue.is_empty() {
+                let l = self.solver.trail[self.bwdsub_assigns];
+                self.bwdsub_assigns += 1;
+                let unit = self.bwdsub_tmpunit;
+                self.solver.ca[unit].lits_mut()[0] = l;
+                self.solver.ca[unit].calc_abstraction();
+                self.subsumption_queue.push_back(unit);
+            }
+
+            let cr = self.subsumption_queue.pop_front().unwrap();
+            if self.solver.ca[cr].mark() != 0 {
+                continue;
+            }
+            // Unit clauses should have been propagated before this point:
+            assert!(
+                self.solver.ca[cr].size() > 1
+                    || self.solver.ca[cr][0].value(&self.solver.assigns) == LBool::True
+            );
+
+            // Find the best variable to scan:
+            let best = self.solver.ca[cr]
+                .iter()
+                .map(|p| p.var())
+                .min_by_key(|&v| self.occurs[v].len())
+                .unwrap();
+
+            // Search all candidates:
+            let ca = &self.solver.ca;
+            self.occurs.clean(best, |&cr| ca[cr].mark() == 1);
+            let mut j = 0;
+            while j < self.occurs[best].len() && self.solver.ca[cr].mark() == 0 {
+                let d = self.occurs[best][j];
+                if d != cr
+                    && self.solver.ca[d].mark() == 0
+                    && (self.subsumption_lim == -1
+                        || self.solver.ca[d].size() < self.subsumption_lim as usize)
+                {
+                    match self.solver.ca[cr].subsumes(&self.solver.ca[d]) {
+                        Some(Lit::UNDEF) => {
+                            self.subsumed_clauses += 1;
+                            self.remove_clause(d);
+                        }
+                        Some(l) => {
+                            self.deleted_literals += 1;
+                            if !self.strengthen_clause(d, !l) {
+                                return false;
+                            }
+                            // The candidate left the list, so check index j again:
+                            if self.occurs[best].get(j) != Some(&d) {
+                                continue;
+                            }
+                        }
+                        None => {}
+                    }
+                }
+                j += 1;
+            }
+        }
+        true
+    }
+
+    /// Removes the literal `l` from the original clause. Returns false if the
+    /// clause set became unsatisfiable.
+    fn strengthen_clause(&mut self, cr: CRef, l: Lit) -> bool {
+        assert!(self.solver.decision_level() == 0);
+
+        if self.solver.ca[cr].size() > 2 {
+            self.subsumption_queue.push_back(cr);
+            self.solver.detach_clause(cr, true);
+            self.solver.ca.strengthen(cr, l);
+            self.solver.attach_clause(cr);
+            self.occurs[l.var()].retain(|&d| d != cr);
+            self.n_occ[l.into_usize()] -= 1;
+            self.update_elim_heap(l.var());
+            return true;
+        }
+
+        // A binary clause becomes a unit:
+        let c = &self.solver.ca[cr];
+        let p = if c[0] == l { c[1] } else { c[0] };
+        self.remove_clause(cr);
+        match p.value(&self.solver.assigns) {
+            LBool::True => true,
+            LBool::False => false,
+            LBool::Undefined => {
+                self.solver.unchecked_enqueue(p, CRef::UNDEF);
+                self.solver.propagate() == CRef::UNDEF
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::var::Var,
+        test_utils::{clause, lit, simp_with_clauses, sorted_clauses},
+    };
+
+    /// All variables are frozen, so only subsumption changes the clauses.
+    fn frozen_simp_with_clauses(num_vars: usize, clauses: &[&[i32]]) -> SimpSolver {
+        let mut simp = simp_with_clauses(num_vars, clauses);
+        for v in 0..num_vars {
+            simp.set_frozen(Var::from(v), true);
+        }
+        simp
+    }
+
+    #[test]
+    fn test_removes_subsumed_clauses() {
+        let mut simp = frozen_simp_with_clauses(4, &[&[1, 2, 3], &[1, 2], &[1, 2, 3, 4], &[-1, 3]]);
+
+        assert!(simp.eliminate(false));
+        assert_eq!(
+            sorted_clauses(&simp, &simp.clauses),
+            vec![clause(&[1, 2]), clause(&[-1, 3])]
+        );
+        assert_eq!(simp.subsumed_clauses, 2);
+    }
+
+    #[test]
+    fn test_strengthens_by_self_subsuming_resolution() {
+        let mut simp = frozen_simp_with_clauses(3, &[&[1, 2, 3], &[-1, 2, 3]]);
+
+        assert!(simp.eliminate(false));
+        // The strengthened clause then subsumes the other one:
+        assert_eq!(sorted_clauses(&simp, &simp.clauses), vec![clause(&[2, 3])]);
+        assert_eq!(simp.deleted_literals, 1);
+        assert_eq!(simp.subsumed_clauses, 1);
+        let cr = simp.clauses[0];
+        assert_eq!(
+            simp.ca[cr].abstraction(),
+            1 << lit(2).var().into_usize() | 1 << lit(3).var().into_usize()
+        );
+        // The strengthened clause is watched as a binary clause:
+        assert!(
+            simp.watches_bin[!simp.ca[cr][0]]
+                .iter()
+                .any(|w| w.cref == cr)
+        );
+    }
+
+    #[test]
+    fn test_top_level_assignments_subsume_and_strengthen() {
+        let mut simp = frozen_simp_with_clauses(4, &[&[1, 2, 3], &[-1, 3, 4]]);
+        simp.remove_satisfied = false;
+        assert!(simp.add_clause(clause(&[1])));
+
+        assert!(simp.eliminate(false));
+        assert_eq!(sorted_clauses(&simp, &simp.clauses), vec![clause(&[3, 4])]);
+    }
+
+    #[test]
+    fn test_strengthening_to_a_unit_propagates() {
+        let mut simp = frozen_simp_with_clauses(3, &[&[1, 2], &[1, -2], &[-1, 3]]);
+
+        assert!(simp.eliminate(false));
+        assert_eq!(lit(1).value(&simp.assigns), LBool::True);
+        assert_eq!(lit(3).value(&simp.assigns), LBool::True);
+    }
+
+    #[test]
+    fn test_strengthening_finds_unsatisfiability() {
+        let mut simp = frozen_simp_with_clauses(2, &[&[1, 2], &[1, -2], &[-1, 2], &[-1, -2]]);
+
+        assert!(!simp.eliminate(false));
+        assert!(!simp.ok);
+    }
+}
@@ -0,0 +1,118 @@
+//! Variable State Independent Decaying Sum (Chaff, MiniSat). Variables in
+//! learnt clauses are bumped by `var_inc`, which grows geometrically so that
+//! older bumps decay relative to newer ones.
+
+use crate::models::{branching_heuristic::BranchingHeuristic, heap::Heap, vsids::Vsids};
+
+impl BranchingHeuristic for Vsids {
+    fn new_var(&mut self) {}
+
+    /// Increases the activity of a variable that participated in a conflict.
+    fn on_conflict_var(
+        &mut self,
+        v: usize,
+        _conflicts: u64,
+        activity: &mut [f64],
+        order_heap: &mut Heap,
+    ) {
+        activity[v] += self.var_inc;
+        if activity[v] > 1e100 {
+            // Rescale:
+            for act in activity.iter_mut() {
+                *act *= 1e-100;
+            }
+            self.var_inc *= 1e-100;
+        }
+
+        // Update order_heap with respect to new activity:
+        if order_heap.in_heap(v) {
+            order_heap.decrease(v, activity);
+        }
+    }
+
+    /// Decays all variable activities by increasing the bump of the next ones.
+    fn on_conflict(&mut self) {
+        self.var_inc *= 1.0 / self.var_decay;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::{branching_heuristic::Branching, clause::CRef, lbool::LBool},
+        test_utils::{lit, solver_with_branching},
+    };
+
+    use super::*;
+
+    fn vsids_with_vars(n: usize) -> (Vsids, Vec<f64>, Heap) {
+        let activity = vec![0.0; n];
+        let mut order_heap = Heap::new();
+        for v in 0..n {
+            order_heap.insert(v, &activity);
+        }
+        (Vsids::default(), activity, order_heap)
+    }
+
+    #[test]
+    fn test_bump_moves_var_to_top() {
+        let (mut vsids, mut activity, mut order_heap) = vsids_with_vars(3);
+        vsids.on_conflict_var(2, 0, &mut activity, &mut order_heap);
+        assert_eq!(activity, vec![0.0, 0.0, 1.0]);
+        assert_eq!(order_heap[0], 2);
+    }
+
+    #[test]
+    fn test_decay_grows_increment() {
+        let (mut vsids, mut activity, mut order_heap) = vsids_with_vars(2);
+        vsids.on_conflict();
+        vsids.on_conflict_var(0, 0, &mut activity, &mut order_heap);
+        assert!((activity[0] - 1.0 / 0.95).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_bump_rescales_large_activities() {
+        let (mut vsids, mut activity, mut order_heap) = vsids_with_vars(2);
+        activity[1] = 1e100;
+        vsids.var_inc = 2e100;
+        vsids.on_conflict_var(0, 0, &mut activity, &mut order_heap);
+
+        assert!((activity[0] - 2.0).abs() < 1e-9);
+        assert!((activity[1] - 1.0).abs() < 1e-9);
+        assert!((vsids.var_inc - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_analyze_bumps_conflict_vars() {
+        let mut solver = solver_with_branching(Branching::Vsids, 3, &[&[-1, 2], &[-1, -2]]);
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(1), CRef::UNDEF);
+        let confl = solver.propagate();
+        assert_ne!(confl, CRef::UNDEF);
+
+        let mut learnt = vec![];
+        solver.analyze(confl, &mut learnt);
+        assert_eq!(solver.activity, vec![1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_solve_with_vsids() {
+        let mut solver = solver_with_branching(
+            Branching::Vsids,
+            6,
+            &[
+                &[1, 2],
+                &[3, 4],
+                &[5, 6],
+                &[-1, -3],
+                &[-1, -5],
+                &[-3, -5],
+                &[-2, -4],
+                &[-2, -6],
+                &[-4, -6],
+            ],
+        );
+        assert_eq!(solver.solve(), LBool::False);
+        assert!(solver.activity.iter().any(|&a| a > 1.0));
+    }
+}
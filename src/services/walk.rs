@@ -0,0 +1,181 @@
+//! Stochastic local search on the original clauses, run when rephasing. The
+//! search starts from the saved phases and the best assignment it reaches
+//! becomes the new saved phases.
+
+use crate::models::{
+    lbool::LBool,
+    lit::Lit,
+    solver::Solver,
+    walker::{WalkScoring, Walker},
+};
+
+use super::branching::{drand, irand};
+
+/// ProbSAT picks a literal with probability proportional to
+/// (PROBSAT_EPS + break)^-PROBSAT_CB, tuned for random 3-SAT.
+const PROBSAT_EPS: f64 = 1.0;
+const PROBSAT_CB: f64 = 2.38;
+/// Probability of a random walk step in WalkSAT.
+const WALKSAT_NOISE: f64 = 0.567;
+
+impl Solver {
+    /// Builds a local search over the problem clauses simplified by the
+    /// top-level assignment, starting from the saved phases.
+    fn new_walker(&self) -> Walker {
+        let mut walker = Walker::new(self.polarity.clone());
+        for &cr in self.clauses.iter() {
+            let c = &self.ca[cr];
+            let fixed = |p: &Lit| self.lit_value(p) != LBool::Undefined && self.level(p.var()) == 0;
+            if c.iter()
+                .any(|p| fixed(p) && self.lit_value(p) == LBool::True)
+            {
+                continue;
+            }
+            walker.add_clause(c.iter().filter(|p| !fixed(p)).copied().collect());
+        }
+        walker
+    }
+
+    /// Picks the literal of the falsified clause `c` to flip.
+    fn pick_walk_lit(&mut self, walker: &Walker, c: usize) -> Lit {
+        let lits = &walker.clauses[c];
+        let breaks: Vec<u32> = lits
+            .iter()
+            .map(|p| walker.break_count(p.var().into_usize()))
+            .collect();
+        match self.walk_scoring {
+            WalkScoring::ProbSat => {
+                let scores: Vec<f64> = breaks
+                    .iter()
+                    .map(|&b| (PROBSAT_EPS + b as f64).powf(-PROBSAT_CB))
+                    .collect();
+                let mut r = drand(&mut self.random_seed) * scores.iter().sum::<f64>();
+                for (i, &score) in scores.iter().enumerate() {
+                    if r < score {
+                        return lits[i];
+                    }
+                    r -= score;
+                }
+                *lits.last().unwrap()
+            }
+            WalkScoring::WalkSat => {
+                let min = breaks.iter().copied().min().unwrap();
+                if min > 0 && drand(&mut self.random_seed) < WALKSAT_NOISE {
+                    lits[irand(&mut self.random_seed, lits.len())]
+                } else {
+                    lits[breaks.iter().position(|&b| b == min).unwrap()]
+                }
+            }
+        }
+    }
+
+    /// Runs the local search for at most `walk_flips` flips and saves the
+    /// phases of the assignment with the fewest falsified clauses.
+    pub fn walk(&mut self) {
+        let mut walker = self.new_walker();
+        let mut best = walker.phase.clone();
+        let mut best_unsat = walker.unsat.len();
+        let mut flips = 0;
+        while flips < self.walk_flips && !walker.unsat.is_empty() {
+            let c = walker.unsat[irand(&mut self.random_seed, walker.unsat.len())];
+            let p = self.pick_walk_lit(&walker, c);
+            walker.flip(p.var().into_usize());
+            flips += 1;
+            if walker.unsat.len() < best_unsat {
+                best_unsat = walker.unsat.len();
+                best.clone_from(&walker.phase);
+            }
+        }
+
+        self.polarity = best;
+        self.walks += 1;
+        self.walk_flips_total += flips;
+        self.walk_best_unsat = best_unsat;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::clause::CRef,
+        test_utils::{lit, slices, solver_with_clauses},
+    };
+
+    use super::*;
+
+    /// Whether the saved phases, read as an assignment, satisfy the clauses.
+    fn phases_satisfy(polarity: &[bool], clauses: &[Vec<i32>]) -> bool {
+        clauses.iter().all(|c| {
+            c.iter()
+                .any(|&x| polarity[(x.abs() - 1) as usize] == (x < 0))
+        })
+    }
+
+    /// A satisfiable random 3-SAT formula, planted on the given assignment.
+    fn planted_3sat(num_vars: usize, num_clauses: usize, seed: &mut f64) -> Vec<Vec<i32>> {
+        let model: Vec<bool> = (0..num_vars).map(|_| drand(seed) < 0.5).collect();
+        let mut clauses = vec![];
+        while clauses.len() < num_clauses {
+            let c: Vec<i32> = (0..3)
+                .map(|_| {
+                    let v = irand(seed, num_vars);
+                    if drand(seed) < 0.5 {
+                        v as i32 + 1
+                    } else {
+                        -(v as i32) - 1
+                    }
+                })
+                .collect();
+            if c.iter().any(|&x| model[(x.abs() - 1) as usize] == (x > 0)) {
+                clauses.push(c);
+            }
+        }
+        clauses
+    }
+
+    #[test]
+    fn test_walk_finds_model() {
+        for scoring in [WalkScoring::ProbSat, WalkScoring::WalkSat] {
+            let clauses = planted_3sat(100, 400, &mut 12345.0);
+            let mut solver = solver_with_clauses(100, &slices(&clauses));
+            solver.walk_scoring = scoring;
+            solver.walk();
+
+            assert_eq!(solver.walk_best_unsat, 0);
+            assert!(phases_satisfy(&solver.polarity, &clauses));
+            assert_eq!(solver.walks, 1);
+            assert!(solver.walk_flips_total > 0);
+        }
+    }
+
+    #[test]
+    fn test_walk_is_deterministic() {
+        let clauses = planted_3sat(50, 215, &mut 777.0);
+        let mut a = solver_with_clauses(50, &slices(&clauses));
+        let mut b = solver_with_clauses(50, &slices(&clauses));
+        a.walk_flips = 20;
+        b.walk_flips = 20;
+        a.walk();
+        b.walk();
+        assert_eq!(a.polarity, b.polarity);
+        assert_eq!(a.walk_best_unsat, b.walk_best_unsat);
+    }
+
+    #[test]
+    fn test_walk_respects_top_level_assignment() {
+        // x1 is fixed true, so the walk only sees (x2 x3) and (-x2 x3).
+        let clauses = vec![vec![1], vec![-1, 2, 3], vec![-2, 3]];
+        let mut solver = solver_with_clauses(3, &slices(&clauses));
+        let walker = solver.new_walker();
+        assert_eq!(
+            walker.clauses,
+            vec![vec![lit(2), lit(3)], vec![lit(-2), lit(3)]]
+        );
+
+        solver.new_decision_level();
+        solver.unchecked_enqueue(lit(-3), CRef::UNDEF);
+        solver.walk();
+        assert_eq!(solver.walk_best_unsat, 0);
+        assert!(!solver.polarity[2]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Helpers shared by the unit tests. Literals and clauses are written in
+//! DIMACS notation: variable 1 is `Var(0)` and a negative number is a
+//! negative literal.
+
+use crate::models::{
+    branching_heuristic::Branching, clause::CRef, lbool::LBool, lit::Lit, simp_solver::SimpSolver,
+    solver::Solver, var::Var,
+};
+
+pub fn lit(dimacs: i32) -> Lit {
+    Lit::new(Var::from(dimacs.abs() - 1), dimacs < 0)
+}
+
+pub fn clause(dimacs: &[i32]) -> Vec<Lit> {
+    dimacs.iter().map(|&x| lit(x)).collect()
+}
+
+pub fn sorted(ps: &[Lit]) -> Vec<Lit> {
+    let mut ps = ps.to_vec();
+    ps.sort();
+    ps
+}
+
+/// The literals of the clauses, each sorted, in sorted order.
+pub fn sorted_clauses(solver: &Solver, crs: &[CRef]) -> Vec<Vec<Lit>> {
+    let mut cs: Vec<Vec<Lit>> = crs.iter().map(|&cr| sorted(solver.ca[cr].lits())).collect();
+    cs.sort();
+    cs
+}
+
+/// Borrows clauses built at run time, for the functions taking literal ones.
+pub fn slices(clauses: &[Vec<i32>]) -> Vec<&[i32]> {
+    clauses.iter().map(Vec::as_slice).collect()
+}
+
+pub fn satisfies<C: AsRef<[i32]>>(model: &[LBool], clauses: &[C]) -> bool {
+    clauses.iter().all(|c| {
+        c.as_ref()
+            .iter()
+            .any(|&x| lit(x).value(model) == LBool::True)
+    })
+}
+
+pub fn solver_with_vars(n: usize) -> Solver {
+    let mut solver = Solver::new();
+    for _ in 0..n {
+        solver.new_var(true, true);
+    }
+    solver
+}
+
+pub fn solver_with_clauses(num_vars: usize, clauses: &[&[i32]]) -> Solver {
+    solver_with_branching(Branching::default(), num_vars, clauses)
+}
+
+pub fn solver_with_branching(branching: Branching, num_vars: usize, clauses: &[&[i32]]) -> Solver {
+    let mut solver = Solver::new().with_branching(branching);
+    for _ in 0..num_vars {
+        solver.new_var(true, true);
+    }
+    for c in clauses {
+        assert!(solver.add_clause(clause(c)));
+    }
+    solver
+}
+
+pub fn simp_with_clauses(num_vars: usize, clauses: &[&[i32]]) -> SimpSolver {
+    let mut simp = SimpSolver::new();
+    for _ in 0..num_vars {
+        simp.new_var(true, true);
+    }
+    for c in clauses {
+        assert!(simp.add_clause(clause(c)));
+    }
+    simp
+}
+
+/// The pigeonhole principle for `pigeons` pigeons and `holes` holes, as the
+/// number of variables and the clauses. Unsatisfiable if there are more
+/// pigeons than holes.
+pub fn pigeonhole(pigeons: i32, holes: i32) -> (usize, Vec<Vec<i32>>) {
+    let var = |p: i32, h: i32| p * holes + h + 1;
+    let mut clauses = vec![];
+    for p in 0..pigeons {
+        clauses.push((0..holes).map(|h| var(p, h)).collect());
+    }
+    for h in 0..holes {
+        for p1 in 0..pigeons {
+            for p2 in p1 + 1..pigeons {
+                clauses.push(vec![-var(p1, h), -var(p2, h)]);
+            }
+        }
+    }
+    ((pigeons * holes) as usize, clauses)
+}